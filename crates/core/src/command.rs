@@ -1,6 +1,16 @@
+use std::collections::HashMap;
+use std::fmt;
 use std::num::NonZeroU64;
+#[cfg(debug_assertions)]
+use std::sync::{Mutex, OnceLock};
+
+use crate::menu::{CommandItem, MenuBarModel, MenuItem, MenuModel};
+use crate::shortcut::ShortcutSequence;
+use crate::tray::TrayMenuModel;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct CommandId(NonZeroU64);
 
 impl CommandId {
@@ -11,4 +21,432 @@ impl CommandId {
     pub fn as_u64(&self) -> u64 {
         self.0.get()
     }
+
+    /// The id for a stable name such as `"file.save"`, the same in every
+    /// process and build.
+    ///
+    /// Named ids lie in `0x8000_0000..=0xFFFF_FFFF`, which every backend
+    /// accepts and `CommandRegistry` never hands out. In debug builds two
+    /// names with the same id panic here; see `try_from_name`.
+    #[track_caller]
+    pub fn from_name(name: &str) -> Self {
+        match Self::try_from_name(name) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Like `from_name`, but returns the clash as an error. Debug builds
+    /// remember every name passed to either function to detect clashes;
+    /// release builds do not check.
+    pub fn try_from_name(name: &str) -> Result<Self, CommandNameCollision> {
+        let id = Self::hash_name(name);
+        #[cfg(debug_assertions)]
+        {
+            static NAMES: OnceLock<Mutex<HashMap<CommandId, String>>> = OnceLock::new();
+            let mut names = NAMES
+                .get_or_init(Default::default)
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            let first = names.entry(id).or_insert_with(|| name.to_string());
+            if first != name {
+                return Err(CommandNameCollision {
+                    first: first.clone(),
+                    second: name.to_string(),
+                    id,
+                });
+            }
+        }
+        Ok(id)
+    }
+
+    /// `from_name` without the collision check, usable in constants.
+    pub const fn hash_name(name: &str) -> Self {
+        // 64-bit FNV-1a, folded to 31 bits. Changing it changes every id.
+        let bytes = name.as_bytes();
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut index = 0;
+        while index < bytes.len() {
+            hash ^= bytes[index] as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            index += 1;
+        }
+        let folded = (hash ^ (hash >> 32)) & 0x7FFF_FFFF;
+        match NonZeroU64::new(0x8000_0000 | folded) {
+            Some(id) => Self(id),
+            None => unreachable!(),
+        }
+    }
+}
+
+/// Two names passed to `CommandId::from_name` hash to the same id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandNameCollision {
+    pub first: String,
+    pub second: String,
+    pub id: CommandId,
+}
+
+impl fmt::Display for CommandNameCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "command names `{}` and `{}` both map to id {:#x}; rename one",
+            self.first,
+            self.second,
+            self.id.as_u64()
+        )
+    }
+}
+
+impl std::error::Error for CommandNameCollision {}
+
+/// What the registry knows about a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandInfo {
+    /// `namespace.command`, e.g. `"file.save"`.
+    pub name: String,
+    /// Used by menu items whose label is empty.
+    pub label: String,
+    /// Used by menu items without a shortcut.
+    pub shortcut: Option<ShortcutSequence>,
+    /// Longer help text, e.g. for a status bar or a command palette.
+    pub description: Option<String>,
+}
+
+impl CommandInfo {
+    pub fn new(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            shortcut: None,
+            description: None,
+        }
+    }
+
+    pub fn with_shortcut(mut self, shortcut: impl Into<ShortcutSequence>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Hands out `CommandId`s by name so separate components cannot collide.
+///
+/// Each namespace (the part of the name before the first `.`) gets its own
+/// block of ids on first use, so its ids do not depend on how many commands
+/// other namespaces register. Blocks are numbered in the order namespaces
+/// first register, so ids are only the same across runs if that order is;
+/// persist names, not ids. Ids below `0x1_0000` are left to hand-picked
+/// constants and never handed out. Ids stay below 2^31, which every backend
+/// accepts; `CommandId::from_name` uses the range above.
+///
+/// ```
+/// # use makepad_shell_core::command::{CommandInfo, CommandRegistry};
+/// # use makepad_shell_core::menu::{CommandItem, MenuItem, MenuModel};
+/// # use makepad_shell_core::shortcut::ShortcutSequence;
+/// let mut registry = CommandRegistry::new();
+/// let shortcut: ShortcutSequence = "CmdOrCtrl+S".parse().unwrap();
+/// let save = registry
+///     .register(CommandInfo::new("file.save", "Save").with_shortcut(shortcut))
+///     .unwrap();
+///
+/// // An empty label and a missing shortcut are taken from the registry.
+/// let menu = MenuModel::new(vec![MenuItem::Command(CommandItem::new(save, ""))]);
+/// let menu = registry.apply_menu(&menu);
+/// # assert!(matches!(&menu.items[0], MenuItem::Command(cmd) if cmd.label == "Save"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry {
+    commands: HashMap<CommandId, CommandInfo>,
+    ids: HashMap<String, CommandId>,
+    namespaces: HashMap<String, NamespaceIds>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NamespaceIds {
+    base: u64,
+    next: u64,
+}
+
+const NAMESPACE_BLOCK: u64 = 0x1_0000;
+// Block 0 holds hand-picked ids, so namespaces start at block 1.
+const NAMESPACE_LIMIT: u64 = 0x7FFF;
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, info: CommandInfo) -> Result<CommandId, CommandRegistryError> {
+        let Some((namespace, command)) = info.name.split_once('.') else {
+            return Err(CommandRegistryError::InvalidName(info.name));
+        };
+        if namespace.is_empty() || command.is_empty() {
+            return Err(CommandRegistryError::InvalidName(info.name));
+        }
+        if self.ids.contains_key(&info.name) {
+            return Err(CommandRegistryError::DuplicateName(info.name));
+        }
+        let block = self.namespaces.len() as u64;
+        if block == NAMESPACE_LIMIT && !self.namespaces.contains_key(namespace) {
+            return Err(CommandRegistryError::TooManyNamespaces);
+        }
+        let ids = self
+            .namespaces
+            .entry(namespace.to_string())
+            .or_insert(NamespaceIds {
+                base: (block + 1) * NAMESPACE_BLOCK,
+                next: 1,
+            });
+        if ids.next == NAMESPACE_BLOCK {
+            return Err(CommandRegistryError::NamespaceFull(namespace.to_string()));
+        }
+        let id = CommandId::new(ids.base + ids.next).expect("namespace ids start at 1");
+        ids.next += 1;
+        self.ids.insert(info.name.clone(), id);
+        self.commands.insert(id, info);
+        Ok(id)
+    }
+
+    pub fn get(&self, id: CommandId) -> Option<&CommandInfo> {
+        self.commands.get(&id)
+    }
+
+    pub fn id(&self, name: &str) -> Option<CommandId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: CommandId) -> Option<&str> {
+        self.get(id).map(|info| info.name.as_str())
+    }
+
+    /// Every registered name with its id, e.g. for `MenuFileLoader::new`.
+    pub fn names(&self) -> &HashMap<String, CommandId> {
+        &self.ids
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (CommandId, &CommandInfo)> + '_ {
+        self.commands.iter().map(|(id, info)| (*id, info))
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// A menu item for the command with its registered label and shortcut.
+    pub fn menu_item(&self, name: &str) -> Option<CommandItem> {
+        let id = self.id(name)?;
+        let info = self.get(id)?;
+        let mut item = CommandItem::new(id, info.label.clone());
+        item.shortcut = info.shortcut;
+        Some(item)
+    }
+
+    /// Fills in registered labels and shortcuts; see `apply_menu_items`.
+    pub fn apply_menu_bar(&self, model: &MenuBarModel) -> MenuBarModel {
+        let mut model = model.clone();
+        for menu in &mut model.menus {
+            self.apply_menu_items(&mut menu.items);
+        }
+        model
+    }
+
+    pub fn apply_menu(&self, model: &MenuModel) -> MenuModel {
+        let mut model = model.clone();
+        self.apply_menu_items(&mut model.items);
+        model
+    }
+
+    pub fn apply_tray_menu(&self, model: &TrayMenuModel) -> TrayMenuModel {
+        let mut model = model.clone();
+        self.apply_menu_items(&mut model.items);
+        model
+    }
+
+    /// Gives items of registered commands the registered label when theirs
+    /// is empty and the registered shortcut when they have none. Apply a
+    /// `Keymap` afterwards so user overrides win.
+    pub fn apply_menu_items(&self, items: &mut [MenuItem]) {
+        for item in items {
+            match item {
+                MenuItem::Command(cmd) => {
+                    let Some(info) = self.get(cmd.id) else {
+                        continue;
+                    };
+                    if cmd.label.is_empty() {
+                        cmd.label = info.label.clone();
+                    }
+                    if cmd.shortcut.is_none() {
+                        cmd.shortcut = info.shortcut;
+                    }
+                }
+                MenuItem::Submenu(sub) => self.apply_menu_items(&mut sub.items),
+                MenuItem::Separator(_) => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandRegistryError {
+    /// Names are `namespace.command` with both parts non-empty.
+    InvalidName(String),
+    DuplicateName(String),
+    /// The namespace has used up its block of ids.
+    NamespaceFull(String),
+    TooManyNamespaces,
+}
+
+impl fmt::Display for CommandRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandRegistryError::InvalidName(name) => {
+                write!(
+                    f,
+                    "invalid command name `{name}`, expected `namespace.command`"
+                )
+            }
+            CommandRegistryError::DuplicateName(name) => {
+                write!(f, "command `{name}` is already registered")
+            }
+            CommandRegistryError::NamespaceFull(namespace) => {
+                write!(f, "no command ids left in namespace `{namespace}`")
+            }
+            CommandRegistryError::TooManyNamespaces => f.write_str("too many command namespaces"),
+        }
+    }
+}
+
+impl std::error::Error for CommandRegistryError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn register(registry: &mut CommandRegistry, name: &str) -> CommandId {
+        registry.register(CommandInfo::new(name, "")).unwrap()
+    }
+
+    #[test]
+    fn namespaces_get_separate_blocks() {
+        let mut registry = CommandRegistry::new();
+        let open = register(&mut registry, "file.open");
+        let copy = register(&mut registry, "edit.copy");
+        let save = register(&mut registry, "file.save");
+        let paste = register(&mut registry, "edit.paste");
+        assert_eq!(open.as_u64(), NAMESPACE_BLOCK + 1);
+        assert_eq!(save.as_u64(), NAMESPACE_BLOCK + 2);
+        assert_eq!(copy.as_u64(), 2 * NAMESPACE_BLOCK + 1);
+        assert_eq!(paste.as_u64(), 2 * NAMESPACE_BLOCK + 2);
+
+        assert_eq!(registry.id("edit.paste"), Some(paste));
+        assert_eq!(registry.name(save), Some("file.save"));
+        assert_eq!(registry.len(), 4);
+    }
+
+    #[test]
+    fn ids_stay_between_hand_picked_and_named_ranges() {
+        let mut registry = CommandRegistry::new();
+        for block in 0..NAMESPACE_LIMIT {
+            let id = register(&mut registry, &format!("n{block}.command"));
+            assert!((NAMESPACE_BLOCK..0x8000_0000).contains(&id.as_u64()));
+        }
+        assert_eq!(
+            registry.register(CommandInfo::new("late.command", "")),
+            Err(CommandRegistryError::TooManyNamespaces)
+        );
+        // Known namespaces keep registering.
+        register(&mut registry, "n0.other");
+    }
+
+    #[test]
+    fn rejects_invalid_and_duplicate_names() {
+        let mut registry = CommandRegistry::new();
+        register(&mut registry, "file.open");
+        for name in ["file", ".open", "file.", ""] {
+            assert_eq!(
+                registry.register(CommandInfo::new(name, "")),
+                Err(CommandRegistryError::InvalidName(name.to_string()))
+            );
+        }
+        assert_eq!(
+            registry.register(CommandInfo::new("file.open", "Open")),
+            Err(CommandRegistryError::DuplicateName("file.open".to_string()))
+        );
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn full_namespace_leaves_others_usable() {
+        let mut registry = CommandRegistry::new();
+        for index in 1..NAMESPACE_BLOCK {
+            register(&mut registry, &format!("big.c{index}"));
+        }
+        assert_eq!(
+            registry.register(CommandInfo::new("big.last", "")),
+            Err(CommandRegistryError::NamespaceFull("big".to_string()))
+        );
+        let other = register(&mut registry, "small.one");
+        assert_eq!(other.as_u64(), 2 * NAMESPACE_BLOCK + 1);
+    }
+
+    #[test]
+    fn apply_fills_empty_labels_and_missing_shortcuts() {
+        let mut registry = CommandRegistry::new();
+        let shortcut: ShortcutSequence = "Ctrl+S".parse().unwrap();
+        let save = registry
+            .register(CommandInfo::new("file.save", "Save").with_shortcut(shortcut))
+            .unwrap();
+        let menu = MenuModel::new(vec![
+            MenuItem::Command(CommandItem::new(save, "")),
+            MenuItem::Command(
+                CommandItem::new(save, "Save Now")
+                    .with_shortcut("Ctrl+Alt+S".parse::<ShortcutSequence>().unwrap()),
+            ),
+        ]);
+        let menu = registry.apply_menu(&menu);
+        let MenuItem::Command(filled) = &menu.items[0] else {
+            unreachable!()
+        };
+        assert_eq!(
+            (filled.label.as_str(), filled.shortcut),
+            ("Save", Some(shortcut))
+        );
+        let MenuItem::Command(kept) = &menu.items[1] else {
+            unreachable!()
+        };
+        assert_eq!(kept.label, "Save Now");
+        assert_ne!(kept.shortcut, Some(shortcut));
+        assert_eq!(
+            registry.menu_item("file.save").unwrap().shortcut,
+            Some(shortcut)
+        );
+    }
+
+    #[test]
+    fn named_ids_are_stable_and_above_registry_range() {
+        let id = CommandId::from_name("test.named");
+        assert_eq!(id, CommandId::hash_name("test.named"));
+        assert_eq!(CommandId::try_from_name("test.named"), Ok(id));
+        assert!((0x8000_0000..=0xFFFF_FFFF).contains(&id.as_u64()));
+        const { assert!((NAMESPACE_LIMIT + 1) * NAMESPACE_BLOCK <= 0x8000_0000) };
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn command_id_serializes_as_its_number() {
+        let id = CommandId::new(42).unwrap();
+        assert_eq!(serde_json::to_string(&id).unwrap(), "42");
+        assert_eq!(serde_json::from_str::<CommandId>("42").unwrap(), id);
+        assert!(serde_json::from_str::<CommandId>("0").is_err());
+    }
 }
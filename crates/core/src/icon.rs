@@ -0,0 +1,25 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Icon {
+    /// `is_template` marks a monochrome image the system tints to match the
+    /// theme (a template image on macOS, a symbolic icon on Linux).
+    Png { bytes: Vec<u8>, is_template: bool },
+}
+
+impl Icon {
+    pub fn from_png_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self::Png {
+            bytes: bytes.into(),
+            is_template: false,
+        }
+    }
+
+    pub fn with_template(mut self, is_template: bool) -> Self {
+        let Icon::Png {
+            is_template: flag, ..
+        } = &mut self;
+        *flag = is_template;
+        self
+    }
+}
@@ -1,9 +1,13 @@
 pub mod error;
 
 pub mod command;
+pub mod icon;
+pub mod locale;
 pub mod menu;
 pub mod notification;
+pub mod platform;
 pub mod shortcut;
 pub mod tray;
 
 pub use error::ShellError;
+pub use platform::Platform;
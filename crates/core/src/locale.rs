@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::menu::{MenuBarModel, MenuItem, MenuModel};
+use crate::notification::Notification;
+use crate::tray::{TrayMenuModel, TrayModel};
+
+/// A message key with arguments, written into a label as
+/// `@key?name=value&name=value`.
+///
+/// Labels that do not start with `@` are shown as they are; `@@` starts a
+/// literal `@`. In keys and values `%`, `&`, `=`, `?` and `@` are written as
+/// `%` and their hex code, which `Display` does.
+///
+/// ```
+/// # use makepad_shell_core::locale::Message;
+/// let message = Message::new("files.selected").with_arg("count", 3);
+/// assert_eq!(message.to_string(), "@files.selected?count=3");
+/// assert_eq!(Message::parse("@files.selected?count=3"), Some(message));
+/// assert_eq!(Message::parse("Open"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub key: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl Message {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_arg(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.args.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// The message in `label`, or `None` for plain text.
+    pub fn parse(label: &str) -> Option<Self> {
+        let rest = label.strip_prefix('@')?;
+        if rest.starts_with('@') {
+            return None;
+        }
+        let (key, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let args = query
+            .split('&')
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| {
+                let (name, value) = arg.split_once('=').unwrap_or((arg, ""));
+                (unescape(name), unescape(value))
+            })
+            .collect();
+        Some(Self {
+            key: unescape(key),
+            args,
+        })
+    }
+
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .find(|(arg, _)| arg == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", escape(&self.key))?;
+        for (index, (name, value)) in self.args.iter().enumerate() {
+            let separator = if index == 0 { '?' } else { '&' };
+            write!(f, "{separator}{}={}", escape(name), escape(value))?;
+        }
+        Ok(())
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '%' => out.push_str("%25"),
+            '&' => out.push_str("%26"),
+            '=' => out.push_str("%3D"),
+            '?' => out.push_str("%3F"),
+            '@' => out.push_str("%40"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('%') {
+        out.push_str(&rest[..pos]);
+        let code = rest.get(pos + 1..pos + 3);
+        match code.and_then(|code| u8::from_str_radix(code, 16).ok()) {
+            Some(byte) if byte.is_ascii() => {
+                out.push(byte as char);
+                rest = &rest[pos + 3..];
+            }
+            _ => {
+                out.push('%');
+                rest = &rest[pos + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Where translations come from, e.g. Fluent bundles or gettext catalogs.
+pub trait MessageCatalog {
+    /// `key` in `locale` with `args` filled in, or `None` if the catalog has
+    /// no such message for that locale.
+    fn format(&self, locale: &str, key: &str, args: &[(String, String)]) -> Option<String>;
+}
+
+impl<F: Fn(&str, &str, &[(String, String)]) -> Option<String>> MessageCatalog for F {
+    fn format(&self, locale: &str, key: &str, args: &[(String, String)]) -> Option<String> {
+        self(locale, key, args)
+    }
+}
+
+/// An in-memory catalog of patterns per locale. `{name}` in a pattern is
+/// replaced by the argument `name`; `{{` and `}}` are literal braces.
+#[derive(Debug, Clone, Default)]
+pub struct MessageTable {
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(
+        &mut self,
+        locale: impl Into<String>,
+        key: impl Into<String>,
+        pattern: impl Into<String>,
+    ) {
+        self.locales
+            .entry(locale.into())
+            .or_default()
+            .insert(key.into(), pattern.into());
+    }
+
+    pub fn with(
+        mut self,
+        locale: impl Into<String>,
+        key: impl Into<String>,
+        pattern: impl Into<String>,
+    ) -> Self {
+        self.insert(locale, key, pattern);
+        self
+    }
+}
+
+impl MessageCatalog for MessageTable {
+    fn format(&self, locale: &str, key: &str, args: &[(String, String)]) -> Option<String> {
+        let pattern = self.locales.get(locale)?.get(key)?;
+        let mut out = String::with_capacity(pattern.len());
+        let mut rest = pattern.as_str();
+        while let Some(pos) = rest.find(['{', '}']) {
+            out.push_str(&rest[..pos]);
+            let tail = &rest[pos..];
+            if tail.starts_with("{{") || tail.starts_with("}}") {
+                out.push_str(&tail[..1]);
+                rest = &tail[2..];
+                continue;
+            }
+            let placeholder = tail
+                .strip_prefix('{')
+                .and_then(|inner| inner.split_once('}'));
+            match placeholder {
+                Some((name, after)) => {
+                    match args.iter().find(|(arg, _)| arg == name.trim()) {
+                        Some((_, value)) => out.push_str(value),
+                        None => out.push_str(&tail[..name.len() + 2]),
+                    }
+                    rest = after;
+                }
+                None => {
+                    out.push_str(&tail[..1]);
+                    rest = &tail[1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        Some(out)
+    }
+}
+
+/// Resolves message labels (`Message`) through a catalog.
+///
+/// A message is looked up in the locale, then in its language (`de` for
+/// `de-AT`), then in the fallback locale; if all miss, its key is shown.
+/// Items of lazy submenus are not in the model, so providers resolve their
+/// own labels.
+///
+/// ```
+/// # use makepad_shell_core::locale::{Localizer, MessageTable};
+/// let catalog = MessageTable::new()
+///     .with("en", "files.selected", "{count} files selected")
+///     .with("en", "file.open", "Open")
+///     .with("de", "file.open", "Öffnen");
+/// let localizer = Localizer::new(catalog, "de-AT", "en");
+/// assert_eq!(localizer.resolve("@file.open"), "Öffnen");
+/// assert_eq!(localizer.resolve("@files.selected?count=2"), "2 files selected");
+/// assert_eq!(localizer.resolve("@@home"), "@home");
+/// ```
+pub struct Localizer {
+    catalog: Box<dyn MessageCatalog>,
+    locale: String,
+    fallback_locale: String,
+}
+
+impl Localizer {
+    pub fn new(
+        catalog: impl MessageCatalog + 'static,
+        locale: impl Into<String>,
+        fallback_locale: impl Into<String>,
+    ) -> Self {
+        Self {
+            catalog: Box::new(catalog),
+            locale: locale.into(),
+            fallback_locale: fallback_locale.into(),
+        }
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    pub fn set_locale(&mut self, locale: impl Into<String>) {
+        self.locale = locale.into();
+    }
+
+    pub fn fallback_locale(&self) -> &str {
+        &self.fallback_locale
+    }
+
+    pub fn format(&self, message: &Message) -> String {
+        self.lookup(message).unwrap_or_else(|| message.key.clone())
+    }
+
+    /// Like `format`, but `None` instead of the key if no locale has the
+    /// message, for text with a default of its own.
+    pub fn lookup(&self, message: &Message) -> Option<String> {
+        let language = self.locale.split(['-', '_']).next().unwrap_or_default();
+        [
+            self.locale.as_str(),
+            language,
+            self.fallback_locale.as_str(),
+        ]
+        .into_iter()
+        .filter(|locale| !locale.is_empty())
+        .find_map(|locale| self.catalog.format(locale, &message.key, &message.args))
+    }
+
+    /// The text to show for `label`.
+    pub fn resolve(&self, label: &str) -> String {
+        if let Some(literal) = label.strip_prefix("@@") {
+            return format!("@{literal}");
+        }
+        match Message::parse(label) {
+            Some(message) => self.format(&message),
+            None => label.to_string(),
+        }
+    }
+
+    pub fn localize_menu_bar(&self, model: &MenuBarModel) -> MenuBarModel {
+        let mut model = model.clone();
+        for menu in &mut model.menus {
+            menu.label = self.resolve(&menu.label);
+            self.localize_menu_items(&mut menu.items);
+        }
+        model
+    }
+
+    pub fn localize_menu(&self, model: &MenuModel) -> MenuModel {
+        let mut model = model.clone();
+        self.localize_menu_items(&mut model.items);
+        model
+    }
+
+    pub fn localize_tray_menu(&self, model: &TrayMenuModel) -> TrayMenuModel {
+        let mut model = model.clone();
+        self.localize_menu_items(&mut model.items);
+        model
+    }
+
+    pub fn localize_tray(&self, model: &TrayModel) -> TrayModel {
+        let mut model = model.clone();
+        model.tooltip = model.tooltip.map(|tooltip| self.resolve(&tooltip));
+        self.localize_menu_items(&mut model.menu.items);
+        model
+    }
+
+    /// Resolves the labels and badges of `items`.
+    pub fn localize_menu_items(&self, items: &mut [MenuItem]) {
+        for item in items {
+            match item {
+                MenuItem::Command(cmd) => {
+                    cmd.label = self.resolve(&cmd.label);
+                    cmd.badge = cmd.badge.as_deref().map(|badge| self.resolve(badge));
+                }
+                MenuItem::Submenu(sub) => {
+                    sub.label = self.resolve(&sub.label);
+                    sub.badge = sub.badge.as_deref().map(|badge| self.resolve(badge));
+                    self.localize_menu_items(&mut sub.items);
+                }
+                MenuItem::Separator(_) => {}
+            }
+        }
+    }
+
+    pub fn localize_notification(&self, notification: &Notification) -> Notification {
+        let mut notification = notification.clone();
+        notification.title = self.resolve(&notification.title);
+        notification.body = notification.body.map(|body| self.resolve(&body));
+        notification.subtitle = notification.subtitle.map(|text| self.resolve(&text));
+        if let Some(button) = &mut notification.action_button {
+            button.label = self.resolve(&button.label);
+        }
+        notification
+    }
+}
+
+impl fmt::Debug for Localizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Localizer")
+            .field("locale", &self.locale)
+            .field("fallback_locale", &self.fallback_locale)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_escapes_round_trip() {
+        let message = Message::new("a@b?c")
+            .with_arg("name", "Tom & Jerry = 100%?")
+            .with_arg("mail", "me@example.com")
+            .with_arg("empty", "");
+        let text = message.to_string();
+        assert_eq!(
+            text,
+            "@a%40b%3Fc?name=Tom %26 Jerry %3D 100%25%3F&mail=me%40example.com&empty="
+        );
+        assert_eq!(Message::parse(&text), Some(message));
+    }
+
+    #[test]
+    fn parses_labels() {
+        assert_eq!(Message::parse("Open"), None);
+        assert_eq!(Message::parse("@@home"), None);
+        assert_eq!(
+            Message::parse("@file.open"),
+            Some(Message::new("file.open"))
+        );
+
+        let message = Message::parse("@files?count=2&&flag").unwrap();
+        assert_eq!(message.arg("count"), Some("2"));
+        assert_eq!(message.arg("flag"), Some(""));
+        assert_eq!(message.args.len(), 2);
+
+        // Malformed or non-ASCII escapes are kept as written.
+        let message = Message::parse("@k?v=50%&w=%zz&x=%C3%A9").unwrap();
+        assert_eq!(message.arg("v"), Some("50%"));
+        assert_eq!(message.arg("w"), Some("%zz"));
+        assert_eq!(message.arg("x"), Some("%C3%A9"));
+    }
+
+    #[test]
+    fn table_formats_placeholders_and_braces() {
+        let table = MessageTable::new()
+            .with("en", "count", "{count} files")
+            .with("en", "braces", "{{count}} is {count}, }} and {{")
+            .with("en", "spaced", "{ count } left")
+            .with("en", "missing", "{other} and {count")
+            .with("en", "stray", "a } b { c");
+        let args = [("count".to_string(), "3".to_string())];
+        let format = |key| table.format("en", key, &args);
+
+        assert_eq!(format("count").as_deref(), Some("3 files"));
+        assert_eq!(format("braces").as_deref(), Some("{count} is 3, } and {"));
+        assert_eq!(format("spaced").as_deref(), Some("3 left"));
+        assert_eq!(format("missing").as_deref(), Some("{other} and {count"));
+        assert_eq!(format("stray").as_deref(), Some("a } b { c"));
+        assert_eq!(table.format("de", "count", &args), None);
+        assert_eq!(format("nope"), None);
+    }
+
+    #[test]
+    fn localizer_falls_back_by_language_then_locale() {
+        let table = MessageTable::new()
+            .with("en", "file.open", "Open")
+            .with("en", "file.save", "Save")
+            .with("de", "file.open", "Öffnen")
+            .with("de-AT", "file.save", "Speichern");
+        let localizer = Localizer::new(table, "de-AT", "en");
+        assert_eq!(localizer.resolve("@file.open"), "Öffnen");
+        assert_eq!(localizer.resolve("@file.save"), "Speichern");
+        assert_eq!(localizer.resolve("@file.close"), "file.close");
+        assert_eq!(localizer.lookup(&Message::new("file.close")), None);
+        assert_eq!(localizer.resolve("Plain"), "Plain");
+
+        let localizer = Localizer::new(|_: &str, _: &str, _: &[_]| None, "fr", "en");
+        assert_eq!(localizer.resolve("@@file.open"), "@file.open");
+    }
+}
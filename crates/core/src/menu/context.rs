@@ -1,4 +1,6 @@
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MenuTrigger {
     MouseRight,
     TouchLongPress,
@@ -6,6 +8,8 @@ pub enum MenuTrigger {
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MenuAnchor {
     Screen { x: f32, y: f32 },
     Window { x: f32, y: f32 },
@@ -0,0 +1,828 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::command::CommandId;
+use crate::icon::Icon;
+use crate::shortcut::ShortcutSequence;
+
+use super::{CheckState, CommandItem, MenuBarModel, MenuItem, MenuItemRole, MenuProvider};
+
+/// One step of turning an old menu tree into a new one.
+///
+/// `parent` is the index path of the menu a structural step applies to (empty
+/// for the root), `path` the index path of the item an update applies to.
+/// Indices are positions at the time the step runs, so steps must be applied
+/// in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuPatchOp {
+    Remove {
+        parent: Vec<usize>,
+        index: usize,
+    },
+    Insert {
+        parent: Vec<usize>,
+        index: usize,
+        item: MenuItem,
+    },
+    /// Take the item at `from` out, then insert it at `to`.
+    Move {
+        parent: Vec<usize>,
+        from: usize,
+        to: usize,
+    },
+    SetLabel {
+        path: Vec<usize>,
+        label: String,
+    },
+    SetIcon {
+        path: Vec<usize>,
+        icon: Option<Icon>,
+    },
+    SetBadge {
+        path: Vec<usize>,
+        badge: Option<String>,
+    },
+    SetEnabled {
+        path: Vec<usize>,
+        enabled: bool,
+    },
+    /// A lazy submenu got a different provider.
+    SetProvider {
+        path: Vec<usize>,
+        provider: MenuProvider,
+    },
+    SetVisible {
+        path: Vec<usize>,
+        visible: bool,
+    },
+    SetChecked {
+        path: Vec<usize>,
+        checked: CheckState,
+    },
+    /// A keyed command kept its key but got a new id.
+    SetCommandId {
+        path: Vec<usize>,
+        id: CommandId,
+    },
+    /// `shortcut` and `role_shortcut` as in `CommandItem`.
+    SetShortcut {
+        path: Vec<usize>,
+        shortcut: Option<ShortcutSequence>,
+        role_shortcut: bool,
+    },
+}
+
+/// Computes the steps that turn `old` into `new`.
+///
+/// Items with a key are matched by key; other commands by id, submenus by
+/// label and separators by position among separators. Commands must also
+/// agree on role, radio group and `alternate_of`. The n-th duplicate in `old`
+/// matches the n-th in `new`. Unmatched items are removed or inserted, and
+/// matched items move only when they are outside the longest run that kept
+/// its order. Per menu the steps are removals, then moves and insertions,
+/// then updates, then the steps of its submenus.
+pub fn diff_menu_items(old: &[MenuItem], new: &[MenuItem]) -> Vec<MenuPatchOp> {
+    let mut ops = Vec::new();
+    diff_items(&mut ops, &[], old, new);
+    ops
+}
+
+/// `diff_menu_items` for every top-level menu; the first index of each path is
+/// the top-level menu. Returns `None` when top-level menus were added, removed,
+/// reordered or changed role, which callers handle by rebuilding.
+pub fn diff_menu_bar(old: &MenuBarModel, new: &MenuBarModel) -> Option<Vec<MenuPatchOp>> {
+    if old.menus.len() != new.menus.len() {
+        return None;
+    }
+    let mut ops = Vec::new();
+    for (index, (old_menu, new_menu)) in old.menus.iter().zip(&new.menus).enumerate() {
+        if old_menu.role != new_menu.role {
+            return None;
+        }
+        if old_menu.label != new_menu.label {
+            ops.push(MenuPatchOp::SetLabel {
+                path: vec![index],
+                label: new_menu.label.clone(),
+            });
+        }
+        diff_items(&mut ops, &[index], &old_menu.items, &new_menu.items);
+    }
+    Some(ops)
+}
+
+/// Applies `ops` to `items`. Returns `false`, leaving `items` partly patched,
+/// if a step does not fit the tree.
+pub fn apply_menu_patch(items: &mut Vec<MenuItem>, ops: &[MenuPatchOp]) -> bool {
+    ops.iter().all(|op| apply_op(items, op))
+}
+
+/// Updates the check marks for an invocation of `id` if its item has
+/// `auto_check` set, and returns the `SetChecked` steps that mirror the change.
+///
+/// A radio item turns on and every other item of its group in the same menu
+/// turns off; any other item toggles, with `Mixed` turning on.
+pub fn auto_check_menu_items(items: &mut [MenuItem], id: CommandId) -> Vec<MenuPatchOp> {
+    let mut ops = Vec::new();
+    let mut path = Vec::new();
+    auto_check_items(&mut ops, &mut path, items, id);
+    ops
+}
+
+/// The item at `path`, descending through submenus.
+pub fn menu_item_at<'a>(items: &'a [MenuItem], path: &[usize]) -> Option<&'a MenuItem> {
+    let (&last, parents) = path.split_last()?;
+    let mut items = items;
+    for &index in parents {
+        match items.get(index)? {
+            MenuItem::Submenu(sub) => items = &sub.items,
+            _ => return None,
+        }
+    }
+    items.get(last)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ItemKey<'a> {
+    Separator,
+    Command(CommandId, CommandKind<'a>),
+    /// Label, and whether the submenu is lazy.
+    Submenu(&'a str, bool),
+    KeyedSeparator(&'a str),
+    KeyedCommand(&'a str, CommandKind<'a>),
+    KeyedSubmenu(&'a str, bool),
+}
+
+/// What a native command item is created as: role, radio group and the item
+/// it is an alternate of.
+type CommandKind<'a> = (Option<MenuItemRole>, Option<&'a str>, Option<CommandId>);
+
+fn item_key(item: &MenuItem) -> ItemKey<'_> {
+    match item {
+        MenuItem::Separator(sep) => match &sep.key {
+            Some(key) => ItemKey::KeyedSeparator(key),
+            None => ItemKey::Separator,
+        },
+        MenuItem::Command(cmd) => command_key(cmd),
+        MenuItem::Submenu(sub) => {
+            let lazy = sub.provider.is_some();
+            match &sub.key {
+                Some(key) => ItemKey::KeyedSubmenu(key, lazy),
+                None => ItemKey::Submenu(&sub.label, lazy),
+            }
+        }
+    }
+}
+
+fn command_key(cmd: &CommandItem) -> ItemKey<'_> {
+    let kind = (cmd.role, cmd.radio_group.as_deref(), cmd.alternate_of);
+    match &cmd.key {
+        Some(key) => ItemKey::KeyedCommand(key, kind),
+        None => ItemKey::Command(cmd.id, kind),
+    }
+}
+
+/// Whether `new` can be `old` changed in place, as `diff_menu_items` would
+/// match them, rather than a new native item.
+pub(super) fn same_native_command(old: &CommandItem, new: &CommandItem) -> bool {
+    command_key(old) == command_key(new)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Old(usize),
+    New(usize),
+}
+
+fn diff_items(ops: &mut Vec<MenuPatchOp>, parent: &[usize], old: &[MenuItem], new: &[MenuItem]) {
+    let mut unmatched: HashMap<ItemKey, VecDeque<usize>> = HashMap::new();
+    for (index, item) in old.iter().enumerate() {
+        unmatched
+            .entry(item_key(item))
+            .or_default()
+            .push_back(index);
+    }
+    // For every new item, the old item it keeps.
+    let sources: Vec<Option<usize>> = new
+        .iter()
+        .map(|item| unmatched.get_mut(&item_key(item))?.pop_front())
+        .collect();
+
+    let mut kept = vec![false; old.len()];
+    for &source in sources.iter().flatten() {
+        kept[source] = true;
+    }
+    for index in (0..old.len()).rev() {
+        if !kept[index] {
+            ops.push(MenuPatchOp::Remove {
+                parent: parent.to_vec(),
+                index,
+            });
+        }
+    }
+
+    let matched: Vec<usize> = sources.iter().flatten().copied().collect();
+    let mut stays = vec![false; old.len()];
+    for index in longest_increasing_run(&matched) {
+        stays[matched[index]] = true;
+    }
+
+    // Walking backwards, each item that does not stay goes right before its
+    // successor, which is already in place.
+    let mut current: Vec<Slot> = (0..old.len())
+        .filter(|&index| kept[index])
+        .map(Slot::Old)
+        .collect();
+    for (index, source) in sources.iter().enumerate().rev() {
+        if source.is_some_and(|source| stays[source]) {
+            continue;
+        }
+        let anchor = match sources.get(index + 1) {
+            Some(next) => {
+                let next = next.map_or(Slot::New(index + 1), Slot::Old);
+                position(&current, next)
+            }
+            None => current.len(),
+        };
+        match source {
+            Some(source) => {
+                let from = position(&current, Slot::Old(*source));
+                let to = if from < anchor { anchor - 1 } else { anchor };
+                if from == to {
+                    continue;
+                }
+                let slot = current.remove(from);
+                current.insert(to, slot);
+                ops.push(MenuPatchOp::Move {
+                    parent: parent.to_vec(),
+                    from,
+                    to,
+                });
+            }
+            None => {
+                current.insert(anchor, Slot::New(index));
+                ops.push(MenuPatchOp::Insert {
+                    parent: parent.to_vec(),
+                    index: anchor,
+                    item: new[index].clone(),
+                });
+            }
+        }
+    }
+
+    let mut nested = Vec::new();
+    for (index, source) in sources.iter().enumerate() {
+        let Some(source) = *source else {
+            continue;
+        };
+        let mut path = parent.to_vec();
+        path.push(index);
+        match (&old[source], &new[index]) {
+            (MenuItem::Command(old_cmd), MenuItem::Command(new_cmd)) => {
+                diff_command(ops, path, old_cmd, new_cmd);
+            }
+            (MenuItem::Submenu(old_sub), MenuItem::Submenu(new_sub)) => {
+                if old_sub.label != new_sub.label {
+                    ops.push(MenuPatchOp::SetLabel {
+                        path: path.clone(),
+                        label: new_sub.label.clone(),
+                    });
+                }
+                diff_decoration(
+                    ops,
+                    &path,
+                    (&old_sub.icon, &old_sub.badge),
+                    (&new_sub.icon, &new_sub.badge),
+                );
+                match &new_sub.provider {
+                    // The native items come from the provider, not `items`.
+                    Some(provider) => {
+                        if old_sub.provider.as_ref() != Some(provider) {
+                            ops.push(MenuPatchOp::SetProvider {
+                                path,
+                                provider: provider.clone(),
+                            });
+                        }
+                    }
+                    None => nested.push((path, &old_sub.items, &new_sub.items)),
+                }
+            }
+            _ => {}
+        }
+    }
+    for (path, old_items, new_items) in nested {
+        diff_items(ops, &path, old_items, new_items);
+    }
+}
+
+/// The in-place steps for a matched command at `path`.
+pub(super) fn diff_command(
+    ops: &mut Vec<MenuPatchOp>,
+    path: Vec<usize>,
+    old: &CommandItem,
+    new: &CommandItem,
+) {
+    if old.id != new.id {
+        ops.push(MenuPatchOp::SetCommandId {
+            path: path.clone(),
+            id: new.id,
+        });
+    }
+    if old.label != new.label {
+        ops.push(MenuPatchOp::SetLabel {
+            path: path.clone(),
+            label: new.label.clone(),
+        });
+    }
+    diff_decoration(ops, &path, (&old.icon, &old.badge), (&new.icon, &new.badge));
+    if old.enabled != new.enabled {
+        ops.push(MenuPatchOp::SetEnabled {
+            path: path.clone(),
+            enabled: new.enabled,
+        });
+    }
+    if old.visible != new.visible {
+        ops.push(MenuPatchOp::SetVisible {
+            path: path.clone(),
+            visible: new.visible,
+        });
+    }
+    if old.checked != new.checked {
+        ops.push(MenuPatchOp::SetChecked {
+            path: path.clone(),
+            checked: new.checked,
+        });
+    }
+    if old.shortcut != new.shortcut || old.role_shortcut != new.role_shortcut {
+        ops.push(MenuPatchOp::SetShortcut {
+            path,
+            shortcut: new.shortcut,
+            role_shortcut: new.role_shortcut,
+        });
+    }
+}
+
+type Decoration<'a> = (&'a Option<Icon>, &'a Option<String>);
+
+fn diff_decoration(ops: &mut Vec<MenuPatchOp>, path: &[usize], old: Decoration, new: Decoration) {
+    if old.0 != new.0 {
+        ops.push(MenuPatchOp::SetIcon {
+            path: path.to_vec(),
+            icon: new.0.clone(),
+        });
+    }
+    if old.1 != new.1 {
+        ops.push(MenuPatchOp::SetBadge {
+            path: path.to_vec(),
+            badge: new.1.clone(),
+        });
+    }
+}
+
+fn position(slots: &[Slot], slot: Slot) -> usize {
+    slots
+        .iter()
+        .position(|candidate| *candidate == slot)
+        .expect("diffed item is in the simulated menu")
+}
+
+/// Indices into `values` of a longest strictly increasing subsequence.
+fn longest_increasing_run(values: &[usize]) -> Vec<usize> {
+    // tails[k]: index of the smallest tail of an increasing run of length k + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+    for (index, &value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&tail| values[tail] < value);
+        previous[index] = length.checked_sub(1).map(|before| tails[before]);
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+    let mut run = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(index) = next {
+        run.push(index);
+        next = previous[index];
+    }
+    run.reverse();
+    run
+}
+
+fn auto_check_items(
+    ops: &mut Vec<MenuPatchOp>,
+    path: &mut Vec<usize>,
+    items: &mut [MenuItem],
+    id: CommandId,
+) -> bool {
+    let found = items
+        .iter()
+        .position(|item| matches!(item, MenuItem::Command(cmd) if cmd.id == id && cmd.auto_check));
+    if let Some(index) = found {
+        let MenuItem::Command(cmd) = &items[index] else {
+            unreachable!()
+        };
+        let set_checked = |ops: &mut Vec<MenuPatchOp>, item: &mut MenuItem, at, checked| {
+            if let MenuItem::Command(cmd) = item
+                && cmd.checked != checked
+            {
+                cmd.checked = checked;
+                let mut item_path = path.clone();
+                item_path.push(at);
+                ops.push(MenuPatchOp::SetChecked {
+                    path: item_path,
+                    checked,
+                });
+            }
+        };
+        match cmd.radio_group.clone() {
+            Some(group) => {
+                for (at, item) in items.iter_mut().enumerate() {
+                    let in_group = matches!(
+                        item,
+                        MenuItem::Command(other) if other.radio_group.as_ref() == Some(&group)
+                    );
+                    if in_group {
+                        set_checked(ops, item, at, CheckState::from(at == index));
+                    }
+                }
+            }
+            None => {
+                let checked = CheckState::from(!cmd.checked.is_on());
+                set_checked(ops, &mut items[index], index, checked);
+            }
+        }
+        return true;
+    }
+    for (index, item) in items.iter_mut().enumerate() {
+        if let MenuItem::Submenu(sub) = item
+            && sub.provider.is_none()
+        {
+            path.push(index);
+            let done = auto_check_items(ops, path, &mut sub.items, id);
+            path.pop();
+            if done {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn items_at_mut<'a>(items: &'a mut Vec<MenuItem>, path: &[usize]) -> Option<&'a mut Vec<MenuItem>> {
+    let mut items = items;
+    for &index in path {
+        match items.get_mut(index)? {
+            MenuItem::Submenu(sub) => items = &mut sub.items,
+            _ => return None,
+        }
+    }
+    Some(items)
+}
+
+fn item_at_mut<'a>(items: &'a mut Vec<MenuItem>, path: &[usize]) -> Option<&'a mut MenuItem> {
+    let (&last, parents) = path.split_last()?;
+    items_at_mut(items, parents)?.get_mut(last)
+}
+
+fn apply_op(items: &mut Vec<MenuItem>, op: &MenuPatchOp) -> bool {
+    match op {
+        MenuPatchOp::Remove { parent, index } => match items_at_mut(items, parent) {
+            Some(items) if *index < items.len() => {
+                items.remove(*index);
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::Insert {
+            parent,
+            index,
+            item,
+        } => match items_at_mut(items, parent) {
+            Some(items) if *index <= items.len() => {
+                items.insert(*index, item.clone());
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::Move { parent, from, to } => match items_at_mut(items, parent) {
+            Some(items) if *from < items.len() && *to < items.len() => {
+                let item = items.remove(*from);
+                items.insert(*to, item);
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetLabel { path, label } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.label = label.clone();
+                true
+            }
+            Some(MenuItem::Submenu(sub)) => {
+                sub.label = label.clone();
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetIcon { path, icon } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.icon = icon.clone();
+                true
+            }
+            Some(MenuItem::Submenu(sub)) => {
+                sub.icon = icon.clone();
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetBadge { path, badge } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.badge = badge.clone();
+                true
+            }
+            Some(MenuItem::Submenu(sub)) => {
+                sub.badge = badge.clone();
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetCommandId { path, id } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.id = *id;
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetEnabled { path, enabled } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.enabled = *enabled;
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetProvider { path, provider } => match item_at_mut(items, path) {
+            Some(MenuItem::Submenu(sub)) => {
+                sub.provider = Some(provider.clone());
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetVisible { path, visible } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.visible = *visible;
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetChecked { path, checked } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.checked = *checked;
+                true
+            }
+            _ => false,
+        },
+        MenuPatchOp::SetShortcut {
+            path,
+            shortcut,
+            role_shortcut,
+        } => match item_at_mut(items, path) {
+            Some(MenuItem::Command(cmd)) => {
+                cmd.shortcut = *shortcut;
+                cmd.role_shortcut = *role_shortcut;
+                true
+            }
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::test_items::{cmd, id};
+    use crate::menu::{CommandItem, SeparatorItem, Submenu};
+
+    fn submenu(label: &str, items: Vec<MenuItem>) -> MenuItem {
+        MenuItem::Submenu(Submenu::new(label, items))
+    }
+
+    /// Recent files get their ids by position, as a "Recent Files" list
+    /// rebuilt from a file history would.
+    fn recent_files(paths: &[&str]) -> Vec<MenuItem> {
+        paths
+            .iter()
+            .enumerate()
+            .map(|(index, path)| {
+                MenuItem::Command(CommandItem::new(id(100 + index as u64), *path).with_key(*path))
+            })
+            .collect()
+    }
+
+    /// Diffs `old` into `new`, checks that the steps rebuild `new` and
+    /// returns them.
+    fn round_trip(old: &[MenuItem], new: &[MenuItem]) -> Vec<MenuPatchOp> {
+        let ops = diff_menu_items(old, new);
+        let mut patched = old.to_vec();
+        assert!(apply_menu_patch(&mut patched, &ops), "{ops:?}");
+        assert_eq!(patched, new);
+        ops
+    }
+
+    #[test]
+    fn identical_menus_need_no_steps() {
+        let items = vec![cmd(1, "Open"), MenuItem::separator(), cmd(2, "Quit")];
+        assert!(round_trip(&items, &items).is_empty());
+    }
+
+    #[test]
+    fn insert_at_top_of_keyed_list_is_one_insert() {
+        let old = vec![submenu(
+            "Recent Files",
+            recent_files(&["b.txt", "c.txt", "d.txt"]),
+        )];
+        let new = vec![submenu(
+            "Recent Files",
+            recent_files(&["a.txt", "b.txt", "c.txt", "d.txt"]),
+        )];
+        let ops = round_trip(&old, &new);
+        let structural: Vec<_> = ops
+            .iter()
+            .filter(|op| !matches!(op, MenuPatchOp::SetCommandId { .. }))
+            .collect();
+        assert!(
+            matches!(
+                structural[..],
+                [MenuPatchOp::Insert { parent, index: 0, .. }] if parent == &[0]
+            ),
+            "{ops:?}"
+        );
+    }
+
+    #[test]
+    fn reorder_moves_only_displaced_items() {
+        let old = vec![cmd(1, "A"), cmd(2, "B"), cmd(3, "C"), cmd(4, "D")];
+        let new = vec![cmd(4, "D"), cmd(1, "A"), cmd(2, "B"), cmd(3, "C")];
+        let ops = round_trip(&old, &new);
+        assert_eq!(
+            ops,
+            [MenuPatchOp::Move {
+                parent: vec![],
+                from: 3,
+                to: 0,
+            }]
+        );
+
+        let reversed: Vec<_> = old.iter().rev().cloned().collect();
+        let ops = round_trip(&old, &reversed);
+        assert!(ops.iter().all(|op| matches!(op, MenuPatchOp::Move { .. })));
+    }
+
+    #[test]
+    fn keyed_submenu_label_change_is_set_label() {
+        let old = vec![MenuItem::Submenu(
+            Submenu::new("Recent", vec![cmd(1, "a.txt")]).with_key("recent"),
+        )];
+        let new = vec![MenuItem::Submenu(
+            Submenu::new("Open Recent", vec![cmd(1, "a.txt")]).with_key("recent"),
+        )];
+        assert_eq!(
+            round_trip(&old, &new),
+            [MenuPatchOp::SetLabel {
+                path: vec![0],
+                label: "Open Recent".to_string(),
+            }]
+        );
+
+        // Unkeyed submenus are matched by label, so they are replaced.
+        let old = vec![submenu("Recent", vec![cmd(1, "a.txt")])];
+        let new = vec![submenu("Open Recent", vec![cmd(1, "a.txt")])];
+        round_trip(&old, &new);
+    }
+
+    #[test]
+    fn separators_are_inserted_and_removed() {
+        let without = vec![cmd(1, "Open"), cmd(2, "Quit")];
+        let with = vec![cmd(1, "Open"), MenuItem::separator(), cmd(2, "Quit")];
+        assert_eq!(
+            round_trip(&without, &with),
+            [MenuPatchOp::Insert {
+                parent: vec![],
+                index: 1,
+                item: MenuItem::separator(),
+            }]
+        );
+        assert_eq!(
+            round_trip(&with, &without),
+            [MenuPatchOp::Remove {
+                parent: vec![],
+                index: 1,
+            }]
+        );
+
+        let keyed = |key| MenuItem::Separator(SeparatorItem::with_key(key));
+        round_trip(
+            &[keyed("a"), cmd(1, "Open"), keyed("b")],
+            &[keyed("b"), cmd(1, "Open")],
+        );
+    }
+
+    #[test]
+    fn provider_swap_is_set_provider() {
+        let first = MenuProvider::new(|| vec![cmd(1, "One")]);
+        let second = MenuProvider::new(|| vec![cmd(2, "Two")]);
+        let old = vec![MenuItem::Submenu(Submenu::lazy("Windows", first.clone()))];
+        let new = vec![MenuItem::Submenu(Submenu::lazy("Windows", second.clone()))];
+        assert_eq!(
+            round_trip(&old, &new),
+            [MenuPatchOp::SetProvider {
+                path: vec![0],
+                provider: second,
+            }]
+        );
+        assert!(round_trip(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn nested_updates_use_item_paths() {
+        let mut grid = CommandItem::new(id(3), "Grid");
+        let old = vec![submenu(
+            "View",
+            vec![cmd(2, "Zoom"), MenuItem::Command(grid.clone())],
+        )];
+        grid.checked = CheckState::On;
+        grid.enabled = false;
+        let new = vec![submenu(
+            "View",
+            vec![cmd(2, "Zoom In"), MenuItem::Command(grid)],
+        )];
+        assert_eq!(
+            round_trip(&old, &new),
+            [
+                MenuPatchOp::SetLabel {
+                    path: vec![0, 0],
+                    label: "Zoom In".to_string(),
+                },
+                MenuPatchOp::SetEnabled {
+                    path: vec![0, 1],
+                    enabled: false,
+                },
+                MenuPatchOp::SetChecked {
+                    path: vec![0, 1],
+                    checked: CheckState::On,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_changes_round_trip() {
+        let old = vec![
+            cmd(1, "New"),
+            cmd(2, "Open"),
+            MenuItem::separator(),
+            submenu("Export", vec![cmd(5, "PNG"), cmd(6, "SVG")]),
+            cmd(3, "Close"),
+        ];
+        let new = vec![
+            cmd(2, "Open…"),
+            submenu("Export", vec![cmd(6, "SVG"), cmd(7, "PDF"), cmd(5, "PNG")]),
+            MenuItem::separator(),
+            cmd(4, "Save"),
+            cmd(3, "Close"),
+            MenuItem::separator(),
+        ];
+        round_trip(&old, &new);
+        round_trip(&new, &old);
+    }
+
+    #[test]
+    fn auto_check_toggles_and_switches_radio_groups() {
+        let radio = |n, checked: bool| {
+            let mut item = CommandItem::new(id(n), "Size")
+                .with_radio_group("size")
+                .with_auto_check(true);
+            item.checked = checked.into();
+            MenuItem::Command(item)
+        };
+        let mut items = vec![
+            MenuItem::Command(CommandItem::new(id(1), "Grid").with_auto_check(true)),
+            radio(2, true),
+            radio(3, false),
+        ];
+        let ops = auto_check_menu_items(&mut items, id(3));
+        assert_eq!(
+            ops,
+            [
+                MenuPatchOp::SetChecked {
+                    path: vec![1],
+                    checked: CheckState::Off,
+                },
+                MenuPatchOp::SetChecked {
+                    path: vec![2],
+                    checked: CheckState::On,
+                },
+            ]
+        );
+        auto_check_menu_items(&mut items, id(1));
+        assert!(matches!(&items[0], MenuItem::Command(cmd) if cmd.checked == CheckState::On));
+    }
+}
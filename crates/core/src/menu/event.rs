@@ -1,7 +1,31 @@
 use crate::command::CommandId;
 
-#[derive(Debug, Clone)]
+/// What happens to a shown menu, in order: `Opened`, any `Highlighted`,
+/// `Closed`, then `CommandInvoked` or `Dismissed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MenuEvent {
+    /// A context or tray menu, or a top-level menu of the menu bar, started
+    /// showing. Submenus opening inside it do not count.
+    Opened,
+    /// The item under the pointer or keyboard focus changed; `None` for
+    /// submenus, separators and system items.
+    Highlighted(Option<CommandId>),
+    /// Also sent for key equivalents, without a menu being shown.
     CommandInvoked(CommandId),
+    /// The menu that sent `Opened` stopped showing.
+    Closed,
+    /// A context or tray menu closed without invoking a command.
     Dismissed,
 }
+
+impl MenuEvent {
+    /// The invoked command, for callers that only handle `CommandInvoked`.
+    pub fn command(&self) -> Option<CommandId> {
+        match self {
+            MenuEvent::CommandInvoked(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,660 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use crate::command::CommandId;
+use crate::shortcut::ShortcutSequence;
+use crate::tray::{TrayIcon, TrayMenuModel, TrayModel};
+
+use super::{
+    CheckState, CommandItem, MenuBarModel, MenuItem, MenuItemRole, MenuModel, SeparatorItem,
+    Submenu, TopMenu, TopMenuRole,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuFileFormat {
+    Json,
+    Toml,
+}
+
+impl MenuFileFormat {
+    /// Picks the format from a `.json` or `.toml` extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        if ext.eq_ignore_ascii_case("json") {
+            Some(Self::Json)
+        } else if ext.eq_ignore_ascii_case("toml") {
+            Some(Self::Toml)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reads menu models from JSON or TOML documents.
+///
+/// Items reference commands by name; `commands` maps those names to ids,
+/// e.g. `CommandRegistry::names`, whose `apply_*` then fills in labels and
+/// shortcuts the document leaves out.
+/// A menu bar document looks like this (the TOML form mirrors it):
+///
+/// ```json
+/// {
+///   "menus": [
+///     { "label": "File", "role": "file", "items": [
+///       { "command": "file.open", "label": "Open…", "shortcut": "CmdOrCtrl+O" },
+///       { "separator": true },
+///       { "label": "Open Recent", "items": [] },
+///       { "command": "app.quit", "label": "Quit", "role": "quit" }
+///     ] }
+///   ]
+/// }
+/// ```
+///
+/// Menu and tray documents are a bare `{ "items": [...] }`; a tray document
+/// may add `"tooltip"`. Item keys besides `command` and `label` are optional:
+/// `shortcut` (`ShortcutSequence` syntax), `role`, `enabled`, `visible`,
+/// `alternate_of` (a command name), `checked` (a bool or `"mixed"`),
+/// `radio_group`, `auto_check`, `badge` (also allowed on submenus), and `key`,
+/// the item's stable key (also allowed on submenus and separators).
+/// Errors carry the 1-based line and column of the offending value.
+#[derive(Debug, Clone, Copy)]
+pub struct MenuFileLoader<'a> {
+    commands: &'a HashMap<String, CommandId>,
+}
+
+impl<'a> MenuFileLoader<'a> {
+    pub fn new(commands: &'a HashMap<String, CommandId>) -> Self {
+        Self { commands }
+    }
+
+    pub fn load_menu_bar(
+        &self,
+        text: &str,
+        format: MenuFileFormat,
+    ) -> Result<MenuBarModel, MenuFileError> {
+        let file = parse(text, format, Seed::<MenuBarFile>::new(self.commands))?;
+        Ok(MenuBarModel::new(file.menus))
+    }
+
+    pub fn load_menu(
+        &self,
+        text: &str,
+        format: MenuFileFormat,
+    ) -> Result<MenuModel, MenuFileError> {
+        let file = parse(text, format, Seed::<ItemsFile>::new(self.commands))?;
+        Ok(MenuModel::new(file.items))
+    }
+
+    /// Icons are binary assets, so the caller supplies the tray icon.
+    pub fn load_tray(
+        &self,
+        text: &str,
+        format: MenuFileFormat,
+        icon: TrayIcon,
+    ) -> Result<TrayModel, MenuFileError> {
+        let file = parse(text, format, Seed::<ItemsFile>::new(self.commands))?;
+        let menu = TrayMenuModel::new(file.items);
+        let mut model = TrayModel::new(icon, menu);
+        model.tooltip = file.tooltip;
+        Ok(model)
+    }
+}
+
+// Names are resolved while deserializing so unknown commands are reported at
+// their position like any other error.
+fn parse<T>(
+    text: &str,
+    format: MenuFileFormat,
+    seed: impl for<'de> DeserializeSeed<'de, Value = T>,
+) -> Result<T, MenuFileError> {
+    match format {
+        MenuFileFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_str(text);
+            seed.deserialize(&mut deserializer)
+                .and_then(|value| deserializer.end().map(|()| value))
+                .map_err(|err| MenuFileError {
+                    message: json_message(&err),
+                    line: err.line(),
+                    column: err.column(),
+                })
+        }
+        MenuFileFormat::Toml => toml::Deserializer::parse(text)
+            .and_then(|deserializer| seed.deserialize(deserializer))
+            .map_err(|err| {
+                let offset = err.span().map_or(0, |span| span.start);
+                let (line, column) = line_column(text, offset);
+                MenuFileError {
+                    message: err.message().to_string(),
+                    line,
+                    column,
+                }
+            }),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuFileError {
+    message: String,
+    line: usize,
+    column: usize,
+}
+
+impl MenuFileError {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// 1-based line of the error in the document.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column of the error in the document.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for MenuFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for MenuFileError {}
+
+// serde_json appends " at line L column C"; the position is reported separately.
+fn json_message(err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    let suffix = format!(" at line {} column {}", err.line(), err.column());
+    message
+        .strip_suffix(&suffix)
+        .unwrap_or(&message)
+        .to_string()
+}
+
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+struct MenuBarFile {
+    menus: Vec<TopMenu>,
+}
+
+struct ItemsFile {
+    items: Vec<MenuItem>,
+    tooltip: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum MenuBarField {
+    Menus,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum ItemsField {
+    Items,
+    Tooltip,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum TopMenuField {
+    Label,
+    Role,
+    Items,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum ItemField {
+    Command,
+    Label,
+    Shortcut,
+    Role,
+    Enabled,
+    Visible,
+    AlternateOf,
+    Checked,
+    RadioGroup,
+    AutoCheck,
+    Badge,
+    Items,
+    Separator,
+    Key,
+}
+
+/// `true`/`false`, or `"off"`, `"on"` and `"mixed"`.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum CheckedDef {
+    Bool(bool),
+    State(CheckState),
+}
+
+/// Deserializes a `T`, looking up command names in `commands`.
+struct Seed<'a, T> {
+    commands: &'a HashMap<String, CommandId>,
+    value: PhantomData<T>,
+}
+
+impl<'a, T> Seed<'a, T> {
+    fn new(commands: &'a HashMap<String, CommandId>) -> Self {
+        Self {
+            commands,
+            value: PhantomData,
+        }
+    }
+
+    fn of<U>(&self) -> Seed<'a, U> {
+        Seed::new(self.commands)
+    }
+}
+
+/// Reads the next value into `slot`, rejecting a second `field`.
+fn next_field<'de, A, S>(
+    map: &mut A,
+    slot: &mut Option<S::Value>,
+    field: &'static str,
+    seed: S,
+) -> Result<(), A::Error>
+where
+    A: MapAccess<'de>,
+    S: DeserializeSeed<'de>,
+{
+    if slot.is_some() {
+        return Err(de::Error::duplicate_field(field));
+    }
+    *slot = Some(map.next_value_seed(seed)?);
+    Ok(())
+}
+
+impl<'de> DeserializeSeed<'de> for Seed<'_, CommandId> {
+    type Value = CommandId;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<CommandId, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        self.commands
+            .get(&name)
+            .copied()
+            .ok_or_else(|| de::Error::custom(format!("unknown command `{name}`")))
+    }
+}
+
+impl<'de, 'a, T> DeserializeSeed<'de> for Seed<'a, Vec<T>>
+where
+    Seed<'a, T>: DeserializeSeed<'de, Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a, T> Visitor<'de> for Seed<'a, Vec<T>>
+where
+    Seed<'a, T>: DeserializeSeed<'de, Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(self.of::<T>())? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Seed<'_, MenuBarFile> {
+    type Value = MenuBarFile;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<MenuBarFile, D::Error> {
+        deserializer.deserialize_struct("MenuBarFile", &["menus"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for Seed<'_, MenuBarFile> {
+    type Value = MenuBarFile;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a menu bar document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<MenuBarFile, A::Error> {
+        let mut menus = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                MenuBarField::Menus => {
+                    next_field(&mut map, &mut menus, "menus", self.of::<Vec<TopMenu>>())?
+                }
+            }
+        }
+        Ok(MenuBarFile {
+            menus: menus.ok_or_else(|| de::Error::missing_field("menus"))?,
+        })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Seed<'_, ItemsFile> {
+    type Value = ItemsFile;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<ItemsFile, D::Error> {
+        deserializer.deserialize_struct("ItemsFile", &["items", "tooltip"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for Seed<'_, ItemsFile> {
+    type Value = ItemsFile;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a menu document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ItemsFile, A::Error> {
+        let (mut items, mut tooltip) = (None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                ItemsField::Items => {
+                    next_field(&mut map, &mut items, "items", self.of::<Vec<MenuItem>>())?
+                }
+                ItemsField::Tooltip => next_field(&mut map, &mut tooltip, "tooltip", PhantomData)?,
+            }
+        }
+        Ok(ItemsFile {
+            items: items.unwrap_or_default(),
+            tooltip,
+        })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Seed<'_, TopMenu> {
+    type Value = TopMenu;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<TopMenu, D::Error> {
+        deserializer.deserialize_struct("TopMenu", &["label", "role", "items"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for Seed<'_, TopMenu> {
+    type Value = TopMenu;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a menu")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<TopMenu, A::Error> {
+        let mut label: Option<String> = None;
+        let mut role: Option<TopMenuRole> = None;
+        let mut items = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                TopMenuField::Label => next_field(&mut map, &mut label, "label", PhantomData)?,
+                TopMenuField::Role => next_field(&mut map, &mut role, "role", PhantomData)?,
+                TopMenuField::Items => {
+                    next_field(&mut map, &mut items, "items", self.of::<Vec<MenuItem>>())?
+                }
+            }
+        }
+        let mut menu = TopMenu::new(label.unwrap_or_default(), items.unwrap_or_default());
+        menu.role = role;
+        Ok(menu)
+    }
+}
+
+const ITEM_FIELDS: &[&str] = &[
+    "command",
+    "label",
+    "shortcut",
+    "role",
+    "enabled",
+    "visible",
+    "alternate_of",
+    "checked",
+    "radio_group",
+    "auto_check",
+    "badge",
+    "items",
+    "separator",
+    "key",
+];
+
+impl<'de> DeserializeSeed<'de> for Seed<'_, MenuItem> {
+    type Value = MenuItem;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<MenuItem, D::Error> {
+        deserializer.deserialize_struct("MenuItem", ITEM_FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for Seed<'_, MenuItem> {
+    type Value = MenuItem;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a menu item")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<MenuItem, A::Error> {
+        let mut command = None;
+        let mut label: Option<String> = None;
+        let mut shortcut: Option<ShortcutSequence> = None;
+        let mut role: Option<MenuItemRole> = None;
+        let mut enabled: Option<bool> = None;
+        let mut visible: Option<bool> = None;
+        let mut alternate_of = None;
+        let mut checked: Option<CheckedDef> = None;
+        let mut radio_group: Option<String> = None;
+        let mut auto_check: Option<bool> = None;
+        let mut badge: Option<String> = None;
+        let mut items = None;
+        let mut separator: Option<bool> = None;
+        let mut key: Option<String> = None;
+        while let Some(field) = map.next_key()? {
+            let map = &mut map;
+            match field {
+                ItemField::Command => {
+                    next_field(map, &mut command, "command", self.of::<CommandId>())?
+                }
+                ItemField::Label => next_field(map, &mut label, "label", PhantomData)?,
+                ItemField::Shortcut => next_field(map, &mut shortcut, "shortcut", PhantomData)?,
+                ItemField::Role => next_field(map, &mut role, "role", PhantomData)?,
+                ItemField::Enabled => next_field(map, &mut enabled, "enabled", PhantomData)?,
+                ItemField::Visible => next_field(map, &mut visible, "visible", PhantomData)?,
+                ItemField::AlternateOf => next_field(
+                    map,
+                    &mut alternate_of,
+                    "alternate_of",
+                    self.of::<CommandId>(),
+                )?,
+                ItemField::Checked => next_field(map, &mut checked, "checked", PhantomData)?,
+                ItemField::RadioGroup => {
+                    next_field(map, &mut radio_group, "radio_group", PhantomData)?
+                }
+                ItemField::AutoCheck => {
+                    next_field(map, &mut auto_check, "auto_check", PhantomData)?
+                }
+                ItemField::Badge => next_field(map, &mut badge, "badge", PhantomData)?,
+                ItemField::Items => {
+                    next_field(map, &mut items, "items", self.of::<Vec<MenuItem>>())?
+                }
+                ItemField::Separator => next_field(map, &mut separator, "separator", PhantomData)?,
+                ItemField::Key => next_field(map, &mut key, "key", PhantomData)?,
+            }
+        }
+        if separator.unwrap_or(false) {
+            return Ok(MenuItem::Separator(SeparatorItem { key }));
+        }
+        if let Some(items) = items {
+            if command.is_some() {
+                return Err(de::Error::custom(
+                    "an item cannot have both `command` and `items`",
+                ));
+            }
+            let mut sub = Submenu::new(label.unwrap_or_default(), items);
+            sub.badge = badge;
+            sub.key = key;
+            return Ok(MenuItem::Submenu(sub));
+        }
+        let Some(id) = command else {
+            return Err(de::Error::custom(
+                "an item needs `command`, `items` or `separator`",
+            ));
+        };
+        let mut cmd = CommandItem::new(id, label.unwrap_or_default());
+        cmd.shortcut = shortcut;
+        cmd.role = role;
+        cmd.enabled = enabled.unwrap_or(true);
+        cmd.visible = visible.unwrap_or(true);
+        cmd.alternate_of = alternate_of;
+        cmd.checked = match checked {
+            Some(CheckedDef::Bool(checked)) => checked.into(),
+            Some(CheckedDef::State(state)) => state,
+            None => CheckState::Off,
+        };
+        cmd.radio_group = radio_group;
+        cmd.auto_check = auto_check.unwrap_or(false);
+        cmd.badge = badge;
+        cmd.key = key;
+        Ok(MenuItem::Command(cmd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands() -> HashMap<String, CommandId> {
+        HashMap::from([
+            ("file.open".to_string(), CommandId::new(1).unwrap()),
+            ("app.quit".to_string(), CommandId::new(2).unwrap()),
+        ])
+    }
+
+    fn menu_error(text: &str, format: MenuFileFormat) -> MenuFileError {
+        MenuFileLoader::new(&commands())
+            .load_menu(text, format)
+            .unwrap_err()
+    }
+
+    fn at(err: &MenuFileError) -> (usize, usize) {
+        (err.line(), err.column())
+    }
+
+    #[test]
+    fn loads_json_and_toml_alike() {
+        let commands = commands();
+        let loader = MenuFileLoader::new(&commands);
+        let json = r#"{ "menus": [ { "label": "File", "items": [
+            { "command": "file.open", "label": "Open", "shortcut": "Ctrl+O" },
+            { "separator": true },
+            { "command": "app.quit", "label": "Quit", "role": "quit", "checked": "mixed" }
+        ] } ] }"#;
+        let toml = r#"
+            [[menus]]
+            label = "File"
+            items = [
+                { command = "file.open", label = "Open", shortcut = "Ctrl+O" },
+                { separator = true },
+                { command = "app.quit", label = "Quit", role = "quit", checked = "mixed" },
+            ]
+        "#;
+        let from_json = loader.load_menu_bar(json, MenuFileFormat::Json).unwrap();
+        let from_toml = loader.load_menu_bar(toml, MenuFileFormat::Toml).unwrap();
+        assert_eq!(from_json, from_toml);
+
+        let items = &from_json.menus[0].items;
+        assert_eq!(items.len(), 3);
+        let MenuItem::Command(quit) = &items[2] else {
+            panic!("{:?}", items[2]);
+        };
+        assert_eq!(quit.role, Some(MenuItemRole::Quit));
+        assert_eq!(quit.checked, CheckState::Mixed);
+    }
+
+    #[test]
+    fn json_errors_report_position() {
+        let err = menu_error(
+            "{\n  \"items\": [\n    { \"command\": \"file.save\" }\n  ]\n}",
+            MenuFileFormat::Json,
+        );
+        assert_eq!(err.message(), "unknown command `file.save`");
+        assert_eq!(at(&err), (3, 30));
+
+        let err = menu_error(
+            r#"{ "items": [ { "label": "A", "label": "B" } ] }"#,
+            MenuFileFormat::Json,
+        );
+        assert!(
+            err.message().starts_with("duplicate field `label`"),
+            "{err}"
+        );
+
+        let err = menu_error(r#"{ "items": [] } x"#, MenuFileFormat::Json);
+        assert_eq!(err.message(), "trailing characters");
+        assert_eq!(at(&err), (1, 17));
+
+        let err = MenuFileLoader::new(&commands())
+            .load_menu_bar("{}", MenuFileFormat::Json)
+            .unwrap_err();
+        assert_eq!(err.message(), "missing field `menus`");
+        assert_eq!(at(&err), (1, 2));
+        assert_eq!(err.to_string(), "1:2: missing field `menus`");
+    }
+
+    #[test]
+    fn toml_errors_report_position() {
+        let err = menu_error("[[items]]\ncommand = \"file.save\"\n", MenuFileFormat::Toml);
+        assert_eq!(err.message(), "unknown command `file.save`");
+        assert_eq!(at(&err), (2, 11));
+
+        let err = menu_error(
+            "[[items]]\ncommand = \"file.open\"\nshortcut = \"Ctrl+Nope\"\n",
+            MenuFileFormat::Toml,
+        );
+        assert_eq!(err.message(), "unknown key `Nope` at position 5");
+        assert_eq!(at(&err), (3, 12));
+
+        let err = menu_error(
+            "[[items]]\nlabel = \"A\"\ncolour = 1\n",
+            MenuFileFormat::Toml,
+        );
+        assert!(err.message().starts_with("unknown field `colour`"), "{err}");
+        assert_eq!(err.line(), 3);
+
+        let err = menu_error(
+            "[[items]]\ncommand = \"file.open\"\nitems = []\n",
+            MenuFileFormat::Toml,
+        );
+        assert_eq!(
+            err.message(),
+            "an item cannot have both `command` and `items`"
+        );
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            MenuFileFormat::from_path("menu.JSON"),
+            Some(MenuFileFormat::Json)
+        );
+        assert_eq!(
+            MenuFileFormat::from_path("a/tray.toml"),
+            Some(MenuFileFormat::Toml)
+        );
+        assert_eq!(MenuFileFormat::from_path("menu.yaml"), None);
+    }
+}
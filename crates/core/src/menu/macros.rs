@@ -0,0 +1,350 @@
+//! Support for the `menu!` and `menu_bar!` macros.
+
+use crate::command::CommandId;
+
+/// Accepted as the id argument of `command(...)` in the menu macros.
+pub trait IntoCommandId {
+    fn into_command_id(self) -> CommandId;
+}
+
+impl IntoCommandId for CommandId {
+    fn into_command_id(self) -> CommandId {
+        self
+    }
+}
+
+impl IntoCommandId for u64 {
+    #[track_caller]
+    fn into_command_id(self) -> CommandId {
+        CommandId::new(self).expect("menu command ids must be non-zero")
+    }
+}
+
+impl IntoCommandId for &str {
+    #[track_caller]
+    fn into_command_id(self) -> CommandId {
+        CommandId::from_name(self)
+    }
+}
+
+pub fn command_id(id: impl IntoCommandId) -> CommandId {
+    id.into_command_id()
+}
+
+/// Builds a `MenuModel` from a list of items:
+///
+/// ```
+/// # use makepad_shell_core::menu;
+/// const CMD_COPY: u64 = 1;
+/// const CMD_GRID: u64 = 2;
+/// # let show_grid = true;
+/// let model = menu![
+///     command(CMD_COPY, "Copy", shortcut = "CmdOrCtrl+C"),
+///     separator,
+///     submenu("View") {
+///         command(CMD_GRID, "Show Grid", checked = show_grid),
+///         command(3, "Zoom to Fit", disabled),
+///     },
+/// ];
+/// ```
+///
+/// `submenu(label, ...)` takes any of `icon = expr`, `badge = expr` and
+/// `key = expr` after the label. `lazy_submenu(label, || items)` builds its
+/// items when it opens.
+///
+/// `command(id, label, ...)` takes a `CommandId`, a non-zero `u64` or a name
+/// for `CommandId::from_name`, followed by any of `shortcut = "..."`,
+/// `role = Quit`, `checked`, `checked = expr` (a `bool` or `CheckState`),
+/// `radio_group = expr`, `auto_check`, `disabled`, `enabled = expr`, `hidden`,
+/// `alternate_of = id`, `icon = expr`, `badge = expr` and `key = expr`.
+/// Shortcuts use the `ShortcutSequence` grammar and are parsed at compile
+/// time, so a typo fails the build:
+///
+/// ```compile_fail
+/// # use makepad_shell_core::menu;
+/// let model = menu![command(1, "Save", shortcut = "Ctrl+Sve")];
+/// ```
+#[macro_export]
+macro_rules! menu {
+    ($($items:tt)*) => {
+        $crate::menu::MenuModel::new($crate::__menu_items!(@acc [] $($items)*))
+    };
+}
+
+/// Builds a `MenuBarModel`; each top-level menu is
+/// `menu("Label", role = File) { items }` with the role optional. Items use
+/// the `menu!` syntax.
+#[macro_export]
+macro_rules! menu_bar {
+    ($($menus:tt)*) => {
+        $crate::menu::MenuBarModel::new($crate::__menu_bar_menus!(@acc [] $($menus)*))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __menu_bar_menus {
+    (@acc [$($acc:expr,)*]) => {
+        ::std::vec![$($acc),*]
+    };
+    (@acc [$($acc:expr,)*]
+        menu($label:expr $(, role = $role:ident)?) { $($items:tt)* } $(, $($rest:tt)*)?
+    ) => {
+        $crate::__menu_bar_menus!(@acc [$($acc,)* {
+            #[allow(unused_mut)]
+            let mut menu = $crate::menu::TopMenu::new(
+                $label,
+                $crate::__menu_items!(@acc [] $($items)*),
+            );
+            $(menu.role = ::std::option::Option::Some($crate::menu::TopMenuRole::$role);)?
+            menu
+        },] $($($rest)*)?)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __menu_items {
+    (@acc [$($acc:expr,)*]) => {
+        ::std::vec![$($acc),*]
+    };
+    (@acc [$($acc:expr,)*] separator $(, $($rest:tt)*)?) => {
+        $crate::__menu_items!(@acc [$($acc,)* $crate::menu::MenuItem::separator(),] $($($rest)*)?)
+    };
+    (@acc [$($acc:expr,)*]
+        submenu($label:expr $(, $($attrs:tt)*)?) { $($items:tt)* } $(, $($rest:tt)*)?
+    ) => {
+        $crate::__menu_items!(@acc [$($acc,)* $crate::menu::MenuItem::Submenu({
+            #[allow(unused_mut)]
+            let mut submenu = $crate::menu::Submenu::new(
+                $label,
+                $crate::__menu_items!(@acc [] $($items)*),
+            );
+            $crate::__menu_submenu_attrs!(submenu; $($($attrs)*)?);
+            submenu
+        }),] $($($rest)*)?)
+    };
+    (@acc [$($acc:expr,)*] lazy_submenu($label:expr, $provider:expr) $(, $($rest:tt)*)?) => {
+        $crate::__menu_items!(@acc [$($acc,)* $crate::menu::MenuItem::Submenu(
+            $crate::menu::Submenu::lazy($label, $crate::menu::MenuProvider::new($provider)),
+        ),] $($($rest)*)?)
+    };
+    (@acc [$($acc:expr,)*] command($id:expr, $label:expr $(, $($attrs:tt)*)?) $(, $($rest:tt)*)?) => {
+        $crate::__menu_items!(@acc [$($acc,)* $crate::menu::MenuItem::Command({
+            #[allow(unused_mut)]
+            let mut cmd = $crate::menu::CommandItem::new(
+                $crate::menu::macros::command_id($id),
+                $label,
+            );
+            $crate::__menu_command_attrs!(cmd; $($($attrs)*)?);
+            cmd
+        }),] $($($rest)*)?)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __menu_command_attrs {
+    ($cmd:ident;) => {};
+    ($cmd:ident; shortcut = $shortcut:literal $(, $($rest:tt)*)?) => {
+        $cmd.shortcut = ::std::option::Option::Some(const {
+            match $crate::shortcut::ShortcutSequence::parse_const($shortcut) {
+                ::std::result::Result::Ok(sequence) => sequence,
+                ::std::result::Result::Err(_) => {
+                    ::std::panic!(::std::concat!("invalid shortcut \"", $shortcut, "\""))
+                }
+            }
+        });
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; role = $role:ident $(, $($rest:tt)*)?) => {
+        $cmd.role = ::std::option::Option::Some($crate::menu::MenuItemRole::$role);
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; checked = $checked:expr $(, $($rest:tt)*)?) => {
+        $cmd.checked = ::std::convert::Into::into($checked);
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; checked $(, $($rest:tt)*)?) => {
+        $cmd.checked = $crate::menu::CheckState::On;
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; enabled = $enabled:expr $(, $($rest:tt)*)?) => {
+        $cmd.enabled = $enabled;
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; key = $key:expr $(, $($rest:tt)*)?) => {
+        $cmd.key = ::std::option::Option::Some(::std::convert::Into::into($key));
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; icon = $icon:expr $(, $($rest:tt)*)?) => {
+        $cmd.icon = ::std::option::Option::Some($icon);
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; badge = $badge:expr $(, $($rest:tt)*)?) => {
+        $cmd.badge = ::std::option::Option::Some(::std::convert::Into::into($badge));
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; radio_group = $group:expr $(, $($rest:tt)*)?) => {
+        $cmd.radio_group = ::std::option::Option::Some(::std::convert::Into::into($group));
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; auto_check $(, $($rest:tt)*)?) => {
+        $cmd.auto_check = true;
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; hidden $(, $($rest:tt)*)?) => {
+        $cmd.visible = false;
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; alternate_of = $id:expr $(, $($rest:tt)*)?) => {
+        $cmd.alternate_of = ::std::option::Option::Some($crate::menu::macros::command_id($id));
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+    ($cmd:ident; disabled $(, $($rest:tt)*)?) => {
+        $cmd.enabled = false;
+        $crate::__menu_command_attrs!($cmd; $($($rest)*)?);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __menu_submenu_attrs {
+    ($submenu:ident;) => {};
+    ($submenu:ident; icon = $icon:expr $(, $($rest:tt)*)?) => {
+        $submenu.icon = ::std::option::Option::Some($icon);
+        $crate::__menu_submenu_attrs!($submenu; $($($rest)*)?);
+    };
+    ($submenu:ident; badge = $badge:expr $(, $($rest:tt)*)?) => {
+        $submenu.badge = ::std::option::Option::Some(::std::convert::Into::into($badge));
+        $crate::__menu_submenu_attrs!($submenu; $($($rest)*)?);
+    };
+    ($submenu:ident; key = $key:expr $(, $($rest:tt)*)?) => {
+        $submenu.key = ::std::option::Option::Some(::std::convert::Into::into($key));
+        $crate::__menu_submenu_attrs!($submenu; $($($rest)*)?);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::icon::Icon;
+    use crate::menu::test_items::{id, seq};
+    use crate::menu::{
+        CommandItem, MenuBarModel, MenuItem, MenuItemRole, MenuModel, Submenu, TopMenu, TopMenuRole,
+    };
+
+    use super::*;
+
+    #[test]
+    fn menu_matches_hand_built_model() {
+        let show_grid = false;
+        let model = crate::menu![
+            command(1, "Copy", shortcut = "Ctrl+C"),
+            separator,
+            submenu("View") {
+                command(2, "Show Grid", checked = show_grid, auto_check),
+                command("view.zoom_to_fit", "Zoom to Fit", disabled, key = "fit"),
+            },
+            command(3, "Quit", role = Quit, hidden),
+        ];
+
+        let mut grid = CommandItem::new(id(2), "Show Grid");
+        grid.auto_check = true;
+        let mut fit = CommandItem::new(CommandId::from_name("view.zoom_to_fit"), "Zoom to Fit")
+            .with_key("fit");
+        fit.enabled = false;
+        let expected = MenuModel::new(vec![
+            MenuItem::Command(CommandItem::new(id(1), "Copy").with_shortcut(seq("Ctrl+C"))),
+            MenuItem::separator(),
+            MenuItem::Submenu(Submenu::new(
+                "View",
+                vec![MenuItem::Command(grid), MenuItem::Command(fit)],
+            )),
+            MenuItem::Command(
+                CommandItem::new(id(3), "Quit")
+                    .with_role(MenuItemRole::Quit)
+                    .with_visible(false),
+            ),
+        ]);
+        assert_eq!(model, expected);
+    }
+
+    #[test]
+    fn command_and_submenu_decorations() {
+        let icon = Icon::from_png_bytes([1, 2, 3]);
+        let model = crate::menu![
+            command(1, "Inbox", icon = icon.clone(), badge = "3", radio_group = "box"),
+            command(2, "Inbox (All)", alternate_of = 1, shortcut = "Ctrl+Alt+I"),
+            submenu("Share", icon = icon.clone(), badge = "new", key = "share") {
+                command(3, "Mail"),
+            },
+        ];
+
+        let expected = MenuModel::new(vec![
+            MenuItem::Command(
+                CommandItem::new(id(1), "Inbox")
+                    .with_icon(icon.clone())
+                    .with_badge("3")
+                    .with_radio_group("box"),
+            ),
+            MenuItem::Command(
+                CommandItem::new(id(2), "Inbox (All)")
+                    .with_alternate_of(id(1))
+                    .with_shortcut(seq("Ctrl+Alt+I")),
+            ),
+            MenuItem::Submenu(
+                Submenu::new(
+                    "Share",
+                    vec![MenuItem::Command(CommandItem::new(id(3), "Mail"))],
+                )
+                .with_icon(icon)
+                .with_badge("new")
+                .with_key("share"),
+            ),
+        ]);
+        assert_eq!(model, expected);
+    }
+
+    #[test]
+    fn menu_bar_matches_hand_built_model() {
+        let model = crate::menu_bar![
+            menu("App", role = App) {
+                command(1, "Quit", role = Quit),
+            },
+            menu("Edit") {
+                command(2, "Undo", shortcut = "Ctrl+Z Ctrl+Z"),
+            },
+        ];
+
+        let expected = MenuBarModel::new(vec![
+            TopMenu::new(
+                "App",
+                vec![MenuItem::Command(
+                    CommandItem::new(id(1), "Quit").with_role(MenuItemRole::Quit),
+                )],
+            )
+            .with_role(TopMenuRole::App),
+            TopMenu::new(
+                "Edit",
+                vec![MenuItem::Command(
+                    CommandItem::new(id(2), "Undo").with_shortcut(seq("Ctrl+Z Ctrl+Z")),
+                )],
+            ),
+        ]);
+        assert_eq!(model, expected);
+    }
+
+    #[test]
+    fn lazy_submenu_keeps_its_provider() {
+        let model = crate::menu![lazy_submenu("Recent", || vec![MenuItem::separator()])];
+        let [MenuItem::Submenu(recent)] = model.items.as_slice() else {
+            panic!("expected one submenu");
+        };
+        assert_eq!(recent.label, "Recent");
+        assert!(recent.items.is_empty());
+        assert_eq!(
+            recent.provider.as_ref().map(|provider| provider.items()),
+            Some(vec![MenuItem::separator()])
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use super::MenuItem;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MenuBarModel {
     pub menus: Vec<TopMenu>,
 }
@@ -11,7 +12,8 @@ impl MenuBarModel {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TopMenu {
     pub label: String,
     pub items: Vec<MenuItem>,
@@ -34,6 +36,8 @@ impl TopMenu {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TopMenuRole {
     App,
     File,
@@ -42,3 +46,47 @@ pub enum TopMenuRole {
     Window,
     Help,
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::command::CommandId;
+    use crate::menu::CommandItem;
+    use crate::shortcut::ShortcutSequence;
+
+    #[test]
+    fn menu_bar_matches_golden_json() {
+        let save = CommandItem::new(CommandId::new(1).unwrap(), "Save")
+            .with_shortcut("Ctrl+S".parse::<ShortcutSequence>().unwrap());
+        let model = MenuBarModel::new(vec![
+            TopMenu::new("File", vec![MenuItem::Command(save)]).with_role(TopMenuRole::File),
+        ]);
+        let golden = serde_json::json!({
+            "menus": [{
+                "label": "File",
+                "role": "file",
+                "items": [{"command": {
+                    "id": 1,
+                    "label": "Save",
+                    "icon": null,
+                    "badge": null,
+                    "enabled": true,
+                    "visible": true,
+                    "alternate_of": null,
+                    "checked": "off",
+                    "radio_group": null,
+                    "auto_check": false,
+                    "shortcut": "Ctrl+S",
+                    "role": null,
+                    "role_shortcut": true,
+                    "key": null,
+                }}],
+            }],
+        });
+        assert_eq!(serde_json::to_value(&model).unwrap(), golden);
+        assert_eq!(
+            serde_json::from_value::<MenuBarModel>(golden).unwrap(),
+            model
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::platform::Platform;
+use crate::shortcut::{Key, Modifiers};
+
+use super::{MenuBarModel, MenuItem};
+
+/// A menu label with its access key marker resolved.
+///
+/// Labels mark the access key with `&` before it (`"Save &As…"`); `&&` is a
+/// literal `&`, and so is an `&` before whitespace or at the end
+/// (`"Tom & Jerry"`). Only the first marker counts; later ones are dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MnemonicLabel {
+    /// The label as displayed, without markers.
+    pub text: String,
+    /// Byte offset of the access key in `text`.
+    pub offset: Option<usize>,
+}
+
+impl MnemonicLabel {
+    pub fn parse(label: &str) -> Self {
+        let mut text = String::with_capacity(label.len());
+        let mut offset = None;
+        let mut chars = label.chars().peekable();
+        while let Some(ch) = chars.next() {
+            if ch != '&' {
+                text.push(ch);
+                continue;
+            }
+            match chars.peek().copied() {
+                Some('&') => {
+                    chars.next();
+                    text.push('&');
+                }
+                Some(next) if !next.is_whitespace() => {
+                    if offset.is_none() {
+                        offset = Some(text.len());
+                    }
+                }
+                _ => text.push('&'),
+            }
+        }
+        Self { text, offset }
+    }
+
+    /// The access key, lowercased.
+    pub fn mnemonic(&self) -> Option<char> {
+        let ch = self.text[self.offset?..].chars().next()?;
+        ch.to_lowercase().next()
+    }
+
+    /// The label in the platform's own syntax: `&` markers on Windows, `_`
+    /// markers on Linux (GTK) and plain text on macOS, which has no access
+    /// keys.
+    pub fn text_for(&self, platform: Platform) -> String {
+        let (marker, escaped) = match platform {
+            Platform::MacOs => return self.text.clone(),
+            Platform::Windows => ('&', "&&"),
+            Platform::Linux => ('_', "__"),
+        };
+        let mut out = String::with_capacity(self.text.len() + 1);
+        for (index, ch) in self.text.char_indices() {
+            if Some(index) == self.offset {
+                out.push(marker);
+            }
+            if ch == marker {
+                out.push_str(escaped);
+            } else {
+                out.push(ch);
+            }
+        }
+        out
+    }
+}
+
+/// Resolves access keys for backends that draw their own menus.
+///
+/// Alt+letter opens the top-level menu with that access key; inside an open
+/// menu the plain letter picks an item. The first match in menu order wins.
+/// Try a `ShortcutMatcher` first: as in Win32, explicit shortcuts win over
+/// access keys.
+#[derive(Debug, Clone, Default)]
+pub struct MnemonicMatcher {
+    menus: Vec<Option<char>>,
+}
+
+impl MnemonicMatcher {
+    pub fn from_menu_bar(model: &MenuBarModel) -> Self {
+        Self {
+            menus: model
+                .menus
+                .iter()
+                .map(|menu| MnemonicLabel::parse(&menu.label).mnemonic())
+                .collect(),
+        }
+    }
+
+    /// Index of the top-level menu that Alt+`key` opens. Other modifiers
+    /// besides Shift make it a regular shortcut.
+    pub fn match_key(&self, key: Key, mods: Modifiers) -> Option<usize> {
+        if !mods.alt || mods.ctrl || mods.meta {
+            return None;
+        }
+        let ch = key_char(key)?;
+        self.menus.iter().position(|mnemonic| *mnemonic == Some(ch))
+    }
+
+    /// Index of the enabled, visible command or submenu in an open menu that
+    /// `key` picks.
+    pub fn match_item(items: &[MenuItem], key: Key) -> Option<usize> {
+        let ch = key_char(key)?;
+        items.iter().position(|item| {
+            let label = match item {
+                MenuItem::Command(cmd) if cmd.enabled && cmd.visible => &cmd.label,
+                MenuItem::Submenu(sub) => &sub.label,
+                _ => return false,
+            };
+            MnemonicLabel::parse(label).mnemonic() == Some(ch)
+        })
+    }
+}
+
+fn key_char(key: Key) -> Option<char> {
+    match key.normalized() {
+        Key::Char(ch) => Some(ch),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::CommandId;
+    use crate::menu::{CommandItem, TopMenu};
+
+    fn parse(label: &str) -> (String, Option<usize>) {
+        let parsed = MnemonicLabel::parse(label);
+        (parsed.text, parsed.offset)
+    }
+
+    #[test]
+    fn parses_markers_and_escapes() {
+        assert_eq!(parse("Save &As…"), ("Save As…".to_string(), Some(5)));
+        assert_eq!(parse("&&Copy"), ("&Copy".to_string(), None));
+        assert_eq!(parse("R&&&D"), ("R&D".to_string(), Some(2)));
+        assert_eq!(parse("Tom & Jerry"), ("Tom & Jerry".to_string(), None));
+        assert_eq!(parse("Rock &"), ("Rock &".to_string(), None));
+        // Only the first marker counts.
+        assert_eq!(parse("&File &Edit"), ("File Edit".to_string(), Some(0)));
+        assert_eq!(parse("Plain"), ("Plain".to_string(), None));
+    }
+
+    #[test]
+    fn bare_ampersand_before_a_letter_is_a_marker() {
+        // Labels from before access keys lose the ampersand; `&&` keeps it.
+        let label = MnemonicLabel::parse("R&D");
+        assert_eq!((label.text.as_str(), label.mnemonic()), ("RD", Some('d')));
+        assert_eq!(label.text_for(Platform::MacOs), "RD");
+        assert_eq!(
+            MnemonicLabel::parse("R&&D").text_for(Platform::MacOs),
+            "R&D"
+        );
+        assert_eq!(MnemonicLabel::parse("Q&A").text, "QA");
+    }
+
+    #[test]
+    fn mnemonic_is_lowercased() {
+        assert_eq!(MnemonicLabel::parse("&Open").mnemonic(), Some('o'));
+        assert_eq!(MnemonicLabel::parse("Ü&Ber").mnemonic(), Some('b'));
+        assert_eq!(MnemonicLabel::parse("Open").mnemonic(), None);
+    }
+
+    #[test]
+    fn renders_platform_syntax() {
+        let label = MnemonicLabel::parse("Save_&All && Exit");
+        assert_eq!(label.text_for(Platform::Windows), "Save_&All && Exit");
+        assert_eq!(label.text_for(Platform::Linux), "Save___All & Exit");
+        assert_eq!(label.text_for(Platform::MacOs), "Save_All & Exit");
+        for label in ["&&Copy", "Tom & Jerry", "Rock &", "R&&&D"] {
+            let parsed = MnemonicLabel::parse(label);
+            let windows = parsed.text_for(Platform::Windows);
+            assert_eq!(MnemonicLabel::parse(&windows), parsed, "{label}");
+        }
+    }
+
+    #[test]
+    fn matches_alt_letter_and_items() {
+        let bar = MenuBarModel::new(vec![
+            TopMenu::new("&File", Vec::new()),
+            TopMenu::new("&Edit", Vec::new()),
+        ]);
+        let matcher = MnemonicMatcher::from_menu_bar(&bar);
+        let alt = Modifiers {
+            alt: true,
+            ..Modifiers::NONE
+        };
+        assert_eq!(matcher.match_key(Key::Char('E'), alt), Some(1));
+        assert_eq!(matcher.match_key(Key::Char('e'), Modifiers::NONE), None);
+        assert_eq!(
+            matcher.match_key(Key::Char('f'), Modifiers { ctrl: true, ..alt }),
+            None
+        );
+
+        let id = |n| CommandId::new(n).unwrap();
+        let mut disabled = CommandItem::new(id(1), "&Close");
+        disabled.enabled = false;
+        let items = [
+            MenuItem::Command(disabled),
+            MenuItem::Command(CommandItem::new(id(2), "&Copy")),
+        ];
+        assert_eq!(MnemonicMatcher::match_item(&items, Key::Char('c')), Some(1));
+    }
+}
@@ -1,9 +1,71 @@
 pub mod model;
 pub mod context;
+pub mod diff;
 pub mod event;
+#[cfg(feature = "menu-file")]
+pub mod file;
+#[doc(hidden)]
+pub mod macros;
 pub mod menubar;
+pub mod mnemonic;
+pub mod path;
+pub mod state;
+pub mod validate;
 
+pub use crate::icon::Icon;
 pub use model::*;
 pub use context::*;
+pub use diff::*;
 pub use event::*;
+#[cfg(feature = "menu-file")]
+pub use file::*;
 pub use menubar::*;
+pub use mnemonic::*;
+pub use path::*;
+pub use state::*;
+pub use validate::*;
+
+/// Builders shared by the menu and shortcut tests.
+#[cfg(test)]
+pub(crate) mod test_items {
+    use crate::command::CommandId;
+    use crate::shortcut::{Shortcut, ShortcutSequence};
+
+    use super::{CommandItem, MenuItem};
+
+    pub(crate) fn id(n: u64) -> CommandId {
+        CommandId::new(n).unwrap()
+    }
+
+    pub(crate) fn stroke(s: &str) -> Shortcut {
+        s.parse().unwrap()
+    }
+
+    pub(crate) fn seq(s: &str) -> ShortcutSequence {
+        s.parse().unwrap()
+    }
+
+    pub(crate) fn command(n: u64, label: &str) -> CommandItem {
+        CommandItem::new(id(n), label)
+    }
+
+    pub(crate) fn cmd(n: u64, label: &str) -> MenuItem {
+        MenuItem::Command(command(n, label))
+    }
+
+    pub(crate) fn bound(n: u64, label: &str, shortcut: &str) -> MenuItem {
+        MenuItem::Command(command(n, label).with_shortcut(seq(shortcut)))
+    }
+
+    /// The first item of command `n`, searching submenus too.
+    pub(crate) fn find_command(items: &[MenuItem], n: u64) -> &CommandItem {
+        fn find(items: &[MenuItem], id: CommandId) -> Option<&CommandItem> {
+            items.iter().find_map(|item| match item {
+                MenuItem::Command(cmd) if cmd.id == id => Some(cmd),
+                MenuItem::Submenu(sub) => find(&sub.items, id),
+                _ => None,
+            })
+        }
+        find(items, id(n)).unwrap()
+    }
+}
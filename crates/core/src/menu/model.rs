@@ -1,7 +1,12 @@
+use std::fmt;
+use std::sync::Arc;
+
 use crate::command::CommandId;
-use crate::shortcut::Shortcut;
+use crate::icon::Icon;
+use crate::shortcut::{Key, Modifiers, Shortcut, ShortcutSequence};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MenuModel {
     pub items: Vec<MenuItem>,
 }
@@ -12,21 +17,82 @@ impl MenuModel {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MenuItem {
     Command(CommandItem),
     Submenu(Submenu),
-    Separator,
+    Separator(SeparatorItem),
 }
 
-#[derive(Debug, Clone)]
+impl MenuItem {
+    pub fn separator() -> Self {
+        MenuItem::Separator(SeparatorItem::default())
+    }
+
+    /// The item's stable key, if it has one.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            MenuItem::Command(cmd) => cmd.key.as_deref(),
+            MenuItem::Submenu(sub) => sub.key.as_deref(),
+            MenuItem::Separator(sep) => sep.key.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CommandItem {
     pub id: CommandId,
+    /// `&` marks the access key (`"Save &As…"`) and `&&` is a literal `&`;
+    /// see `MnemonicLabel`. A label such as `"R&D"` shows as `RD`, so write
+    /// `"R&&D"` for the ampersand.
     pub label: String,
+    /// Shown before the label where the platform supports item images.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub icon: Option<Icon>,
+    /// Short trailing text such as an unread count; appended to the label
+    /// where the platform has no badges.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub badge: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default = "default_true"))]
     pub enabled: bool,
-    pub checked: bool,
-    pub shortcut: Option<Shortcut>,
+    /// Hidden items are not shown but their shortcut still works on macOS.
+    /// Backends without hidden items leave them out (`visible_menu_items`).
+    #[cfg_attr(feature = "serde", serde(default = "default_true"))]
+    pub visible: bool,
+    /// Makes this item an alternate of the preceding sibling, whose id this
+    /// is: macOS shows it in that item's place while Option is held. Give it
+    /// the same shortcut plus Alt, or none. Backends without alternates leave
+    /// it out.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub alternate_of: Option<CommandId>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checked: CheckState,
+    /// Items of the same group within one menu are mutually exclusive choices.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub radio_group: Option<String>,
+    /// The backend updates `checked` itself when the item is invoked: a
+    /// checkbox toggles, a radio item turns on and turns its group off.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub auto_check: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shortcut: Option<ShortcutSequence>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub role: Option<MenuItemRole>,
+    /// Whether the role's `default_shortcut` applies when `shortcut` is `None`.
+    #[cfg_attr(feature = "serde", serde(default = "default_true"))]
+    pub role_shortcut: bool,
+    /// Identity across model updates, unique among siblings. Keyed items are
+    /// matched by key when diffing, so the id may change between updates.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub key: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+fn default_true() -> bool {
+    true
 }
 
 impl CommandItem {
@@ -34,10 +100,18 @@ impl CommandItem {
         Self {
             id,
             label: label.into(),
+            icon: None,
+            badge: None,
             enabled: true,
-            checked: false,
+            visible: true,
+            alternate_of: None,
+            checked: CheckState::Off,
+            radio_group: None,
+            auto_check: false,
             shortcut: None,
             role: None,
+            role_shortcut: true,
+            key: None,
         }
     }
 
@@ -45,24 +119,196 @@ impl CommandItem {
         self.role = Some(role);
         self
     }
+
+    pub fn with_shortcut(mut self, shortcut: impl Into<ShortcutSequence>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn with_badge(mut self, badge: impl Into<String>) -> Self {
+        self.badge = Some(badge.into());
+        self
+    }
+
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    pub fn with_alternate_of(mut self, id: CommandId) -> Self {
+        self.alternate_of = Some(id);
+        self
+    }
+
+    pub fn with_radio_group(mut self, group: impl Into<String>) -> Self {
+        self.radio_group = Some(group.into());
+        self
+    }
+
+    pub fn with_auto_check(mut self, auto_check: bool) -> Self {
+        self.auto_check = auto_check;
+        self
+    }
+}
+
+/// The items a backend without hidden or alternate items shows: `items`
+/// without hidden commands and alternates, at every level.
+pub fn visible_menu_items(items: &[MenuItem]) -> Vec<MenuItem> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            MenuItem::Command(cmd) if !cmd.visible || cmd.alternate_of.is_some() => None,
+            MenuItem::Submenu(sub) => {
+                let mut sub = sub.clone();
+                sub.items = visible_menu_items(&sub.items);
+                Some(MenuItem::Submenu(sub))
+            }
+            item => Some(item.clone()),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CheckState {
+    #[default]
+    Off,
+    On,
+    /// Partly on, e.g. bold for a selection that is only partly bold.
+    /// Windows has no mixed mark and shows it as `On`.
+    Mixed,
+}
+
+impl CheckState {
+    pub fn is_on(self) -> bool {
+        self == CheckState::On
+    }
+}
+
+impl From<bool> for CheckState {
+    fn from(checked: bool) -> Self {
+        if checked {
+            CheckState::On
+        } else {
+            CheckState::Off
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Submenu {
+    /// Marks its access key like `CommandItem::label`.
     pub label: String,
+    pub icon: Option<Icon>,
+    pub badge: Option<String>,
     pub items: Vec<MenuItem>,
+    /// Replaces `items` with fresh ones each time the submenu opens.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub provider: Option<MenuProvider>,
+    /// Identity across model updates; without one, submenus are matched by label.
+    pub key: Option<String>,
 }
 
 impl Submenu {
     pub fn new(label: impl Into<String>, items: Vec<MenuItem>) -> Self {
         Self {
             label: label.into(),
+            icon: None,
+            badge: None,
             items,
+            provider: None,
+            key: None,
+        }
+    }
+
+    /// A submenu whose items come from `provider` when it is about to open.
+    pub fn lazy(label: impl Into<String>, provider: MenuProvider) -> Self {
+        let mut submenu = Self::new(label, Vec::new());
+        submenu.provider = Some(provider);
+        submenu
+    }
+
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn with_badge(mut self, badge: impl Into<String>) -> Self {
+        self.badge = Some(badge.into());
+        self
+    }
+
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+}
+
+/// Builds a lazy submenu's items. macOS calls it from `menuNeedsUpdate:`;
+/// backends without an open notification call it when the whole menu pops
+/// up.
+///
+/// Provided items are rebuilt on every open: they are not diffed, auto-check
+/// does not reach them, and their shortcuts only work while the menu is open.
+/// Providers compare equal only when they are clones of each other.
+#[derive(Clone)]
+pub struct MenuProvider(Arc<dyn Fn() -> Vec<MenuItem> + Send + Sync>);
+
+impl MenuProvider {
+    pub fn new(provider: impl Fn() -> Vec<MenuItem> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+
+    pub fn items(&self) -> Vec<MenuItem> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for MenuProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MenuProvider(..)")
+    }
+}
+
+impl PartialEq for MenuProvider {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for MenuProvider {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeparatorItem {
+    /// Identity across model updates; without one, separators are matched by
+    /// their order among separators.
+    pub key: Option<String>,
+}
+
+impl SeparatorItem {
+    pub fn with_key(key: impl Into<String>) -> Self {
+        Self {
+            key: Some(key.into()),
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MenuItemRole {
     About,
     Preferences,
@@ -75,3 +321,76 @@ pub enum MenuItemRole {
     Zoom,
     BringAllToFront,
 }
+
+impl MenuItemRole {
+    /// The key equivalent AppKit conventionally gives this role (Cmd+Q for
+    /// Quit, ...). macOS backends apply it when the item has no shortcut.
+    pub fn default_shortcut(self) -> Option<Shortcut> {
+        let cmd = Modifiers {
+            meta: true,
+            ..Modifiers::NONE
+        };
+        let (mods, key) = match self {
+            MenuItemRole::Preferences => (cmd, ','),
+            MenuItemRole::Hide => (cmd, 'h'),
+            MenuItemRole::HideOthers => (Modifiers { alt: true, ..cmd }, 'h'),
+            MenuItemRole::Quit => (cmd, 'q'),
+            MenuItemRole::Minimize => (cmd, 'm'),
+            MenuItemRole::About
+            | MenuItemRole::Services
+            | MenuItemRole::ShowAll
+            | MenuItemRole::Zoom
+            | MenuItemRole::BringAllToFront => return None,
+        };
+        Some(Shortcut::new(mods, Key::Char(key)))
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_item_fields_default_like_new() {
+        let item: CommandItem = serde_json::from_str(r#"{"id":1,"label":"Open"}"#).unwrap();
+        assert_eq!(item, CommandItem::new(CommandId::new(1).unwrap(), "Open"));
+    }
+
+    #[test]
+    fn roles_and_check_states_are_snake_case() {
+        let json =
+            serde_json::to_string(&[MenuItemRole::HideOthers, MenuItemRole::BringAllToFront])
+                .unwrap();
+        assert_eq!(json, r#"["hide_others","bring_all_to_front"]"#);
+        assert_eq!(
+            serde_json::from_str::<MenuItemRole>(r#""show_all""#).unwrap(),
+            MenuItemRole::ShowAll
+        );
+        assert_eq!(
+            serde_json::to_string(&CheckState::Mixed).unwrap(),
+            r#""mixed""#
+        );
+    }
+
+    #[test]
+    fn menu_model_round_trips() {
+        let id = |n| CommandId::new(n).unwrap();
+        let model = MenuModel::new(vec![
+            MenuItem::Command(
+                CommandItem::new(id(1), "Quit")
+                    .with_role(MenuItemRole::Quit)
+                    .with_shortcut("Ctrl+Q".parse::<ShortcutSequence>().unwrap()),
+            ),
+            MenuItem::separator(),
+            MenuItem::Submenu(
+                Submenu::new(
+                    "Recent",
+                    vec![MenuItem::Command(CommandItem::new(id(2), "a.txt"))],
+                )
+                .with_key("recent"),
+            ),
+        ]);
+        let json = serde_json::to_string(&model).unwrap();
+        assert_eq!(serde_json::from_str::<MenuModel>(&json).unwrap(), model);
+    }
+}
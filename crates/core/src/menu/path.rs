@@ -0,0 +1,45 @@
+use std::fmt;
+
+/// Location of an item in a menu tree: child indices from the root, with the
+/// label of every step for display (`"File > Open Recent > Clear"`).
+///
+/// For a `MenuBarModel` the first step is the top-level menu.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MenuPath {
+    pub indices: Vec<usize>,
+    pub labels: Vec<String>,
+}
+
+impl MenuPath {
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn child(&self, index: usize, label: &str) -> Self {
+        let mut path = self.clone();
+        path.indices.push(index);
+        path.labels.push(label.to_string());
+        path
+    }
+
+    pub fn depth(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+impl fmt::Display for MenuPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (step, (index, label)) in self.indices.iter().zip(&self.labels).enumerate() {
+            if step > 0 {
+                f.write_str(" > ")?;
+            }
+            if label.is_empty() {
+                write!(f, "#{index}")?;
+            } else {
+                f.write_str(label)?;
+            }
+        }
+        Ok(())
+    }
+}
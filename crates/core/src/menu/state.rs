@@ -0,0 +1,254 @@
+use crate::command::CommandId;
+
+use super::diff::{diff_command, same_native_command};
+use super::{CheckState, CommandItem, MenuItem, MenuPatchOp};
+
+/// A command's current state. `None` leaves the item as it is, so an answer
+/// stays until a later one or a model update replaces it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandState {
+    pub label: Option<String>,
+    pub enabled: Option<bool>,
+    pub checked: Option<CheckState>,
+}
+
+impl CommandState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    pub fn with_checked(mut self, checked: impl Into<CheckState>) -> Self {
+        self.checked = Some(checked.into());
+        self
+    }
+}
+
+/// Asked for each command's state right before its menu shows, so menus
+/// need not be rebuilt whenever a flag changes.
+///
+/// macOS asks from `validateMenuItem:`, which AppKit also calls before a key
+/// equivalent fires; other backends ask when the whole menu pops up.
+pub trait CommandStateProvider {
+    fn command_state(&self, id: CommandId) -> CommandState;
+}
+
+impl<F: Fn(CommandId) -> CommandState> CommandStateProvider for F {
+    fn command_state(&self, id: CommandId) -> CommandState {
+        self(id)
+    }
+}
+
+/// Asks `provider` about every command in `items`, updates them and returns
+/// the `SetLabel`, `SetEnabled` and `SetChecked` steps that mirror the
+/// changes.
+pub fn apply_command_state(
+    items: &mut [MenuItem],
+    provider: &dyn CommandStateProvider,
+) -> Vec<MenuPatchOp> {
+    let mut ops = Vec::new();
+    update_items(&mut ops, &mut Vec::new(), items, &mut |cmd| {
+        let state = provider.command_state(cmd.id);
+        if let Some(label) = state.label {
+            cmd.label = label;
+        }
+        if let Some(enabled) = state.enabled {
+            cmd.enabled = enabled;
+        }
+        if let Some(checked) = state.checked {
+            cmd.checked = checked;
+        }
+    });
+    ops
+}
+
+/// Runs `update` on every item of command `id` and returns the steps that
+/// mirror the changes, the same `diff_menu_items` would. An item whose role,
+/// radio group, `alternate_of` or key changed is replaced: a `Remove` and an
+/// `Insert` at its index. Items of lazy submenus are not in `items` and are
+/// left alone.
+pub fn update_command_items(
+    items: &mut [MenuItem],
+    id: CommandId,
+    mut update: impl FnMut(&mut CommandItem),
+) -> Vec<MenuPatchOp> {
+    let mut ops = Vec::new();
+    update_items(&mut ops, &mut Vec::new(), items, &mut |cmd| {
+        if cmd.id == id {
+            update(cmd);
+        }
+    });
+    ops
+}
+
+fn update_items(
+    ops: &mut Vec<MenuPatchOp>,
+    path: &mut Vec<usize>,
+    items: &mut [MenuItem],
+    update: &mut dyn FnMut(&mut CommandItem),
+) {
+    for (index, item) in items.iter_mut().enumerate() {
+        path.push(index);
+        match item {
+            MenuItem::Command(cmd) => {
+                let old = cmd.clone();
+                update(cmd);
+                if same_native_command(&old, cmd) {
+                    diff_command(ops, path.clone(), &old, cmd);
+                } else {
+                    let parent = path[..path.len() - 1].to_vec();
+                    ops.push(MenuPatchOp::Remove {
+                        parent: parent.clone(),
+                        index,
+                    });
+                    ops.push(MenuPatchOp::Insert {
+                        parent,
+                        index,
+                        item: MenuItem::Command(cmd.clone()),
+                    });
+                }
+            }
+            MenuItem::Submenu(sub) => update_items(ops, path, &mut sub.items, update),
+            MenuItem::Separator(_) => {}
+        }
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::test_items::{cmd, id};
+    use crate::menu::{MenuItemRole, Submenu, apply_menu_patch};
+
+    fn items() -> Vec<MenuItem> {
+        vec![
+            cmd(1, "Save"),
+            MenuItem::separator(),
+            MenuItem::Submenu(Submenu::new(
+                "View",
+                vec![
+                    cmd(2, "Grid"),
+                    MenuItem::Submenu(Submenu::new(
+                        "Zoom",
+                        vec![cmd(3, "Zoom In"), cmd(2, "Grid")],
+                    )),
+                ],
+            )),
+        ]
+    }
+
+    /// The ops must turn the items as they were into the items as updated.
+    fn assert_mirrors(old: Vec<MenuItem>, new: &[MenuItem], ops: &[MenuPatchOp]) {
+        let mut patched = old;
+        assert!(apply_menu_patch(&mut patched, ops));
+        assert_eq!(patched, new);
+    }
+
+    #[test]
+    fn command_state_ops_follow_nested_items() {
+        let mut menu = items();
+        let provider = |cmd: CommandId| match cmd.as_u64() {
+            2 => CommandState::new().with_checked(true),
+            3 => CommandState::new()
+                .with_label("Zoom In (200%)")
+                .with_enabled(false),
+            _ => CommandState::new(),
+        };
+        let ops = apply_command_state(&mut menu, &provider);
+        assert_eq!(
+            ops,
+            vec![
+                MenuPatchOp::SetChecked {
+                    path: vec![2, 0],
+                    checked: CheckState::On,
+                },
+                MenuPatchOp::SetLabel {
+                    path: vec![2, 1, 0],
+                    label: "Zoom In (200%)".to_string(),
+                },
+                MenuPatchOp::SetEnabled {
+                    path: vec![2, 1, 0],
+                    enabled: false,
+                },
+                MenuPatchOp::SetChecked {
+                    path: vec![2, 1, 1],
+                    checked: CheckState::On,
+                },
+            ]
+        );
+        assert_mirrors(items(), &menu, &ops);
+
+        // Answers that match the items change nothing.
+        assert!(apply_command_state(&mut menu, &provider).is_empty());
+    }
+
+    #[test]
+    fn update_command_ops_follow_every_item_of_the_command() {
+        let mut menu = items();
+        let ops = update_command_items(&mut menu, id(2), |cmd| {
+            cmd.label = "Show Grid".to_string();
+            cmd.visible = false;
+        });
+        assert_eq!(
+            ops,
+            vec![
+                MenuPatchOp::SetLabel {
+                    path: vec![2, 0],
+                    label: "Show Grid".to_string(),
+                },
+                MenuPatchOp::SetVisible {
+                    path: vec![2, 0],
+                    visible: false,
+                },
+                MenuPatchOp::SetLabel {
+                    path: vec![2, 1, 1],
+                    label: "Show Grid".to_string(),
+                },
+                MenuPatchOp::SetVisible {
+                    path: vec![2, 1, 1],
+                    visible: false,
+                },
+            ]
+        );
+        assert_mirrors(items(), &menu, &ops);
+    }
+
+    #[test]
+    fn update_command_replaces_items_it_cannot_patch() {
+        let mut menu = items();
+        let ops = update_command_items(&mut menu, id(3), |cmd| {
+            cmd.role = Some(MenuItemRole::Minimize);
+        });
+        let MenuItem::Submenu(view) = &menu[2] else {
+            unreachable!()
+        };
+        let MenuItem::Submenu(zoom) = &view.items[1] else {
+            unreachable!()
+        };
+        assert_eq!(
+            ops,
+            vec![
+                MenuPatchOp::Remove {
+                    parent: vec![2, 1],
+                    index: 0,
+                },
+                MenuPatchOp::Insert {
+                    parent: vec![2, 1],
+                    index: 0,
+                    item: zoom.items[0].clone(),
+                },
+            ]
+        );
+        assert_mirrors(items(), &menu, &ops);
+    }
+}
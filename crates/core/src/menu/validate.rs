@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::command::CommandId;
+use crate::platform::Platform;
+use crate::shortcut::{Key, ShortcutSequence};
+use crate::tray::TrayModel;
+
+use super::{MenuBarModel, MenuItem, MenuItemRole, MenuModel, MenuPath, TopMenuRole};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The backend accepts the model, but not the way it reads.
+    Warning,
+    /// The backend rejects the whole menu.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuDiagnosticKind {
+    /// Another item already uses this id; events cannot tell them apart.
+    DuplicateCommandId {
+        first: MenuPath,
+    },
+    /// Another item or top-level menu already has this role. macOS keeps only
+    /// the first one in the app menu.
+    DuplicateRole {
+        first: MenuPath,
+    },
+    /// A sibling already has this key; diffing cannot tell them apart.
+    DuplicateKey {
+        first: MenuPath,
+    },
+    /// Another item of the same radio group is already on.
+    RadioGroupConflict {
+        first: MenuPath,
+    },
+    /// `alternate_of` does not name the preceding sibling command.
+    DetachedAlternate,
+    EmptyLabel,
+    /// A submenu or top-level menu without items.
+    EmptySubmenu,
+    /// A leading, trailing or repeated separator.
+    RedundantSeparator,
+    /// The id does not fit the platform's native item id.
+    CommandIdOutOfRange {
+        limit: u64,
+    },
+    /// The platform cannot install this shortcut; it is dropped or only shown
+    /// next to the label.
+    UnrepresentableShortcut(ShortcutSequence),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuDiagnostic {
+    pub kind: MenuDiagnosticKind,
+    pub path: MenuPath,
+}
+
+impl MenuDiagnostic {
+    pub fn severity(&self) -> Severity {
+        match self.kind {
+            MenuDiagnosticKind::CommandIdOutOfRange { .. } => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for MenuDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity() {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: {}: ", self.path)?;
+        match &self.kind {
+            MenuDiagnosticKind::DuplicateCommandId { first } => {
+                write!(f, "command id already used by {first}")
+            }
+            MenuDiagnosticKind::DuplicateRole { first } => {
+                write!(f, "role already used by {first}")
+            }
+            MenuDiagnosticKind::DuplicateKey { first } => {
+                write!(f, "key already used by {first}")
+            }
+            MenuDiagnosticKind::RadioGroupConflict { first } => {
+                write!(f, "radio group already has {first} checked")
+            }
+            MenuDiagnosticKind::DetachedAlternate => {
+                f.write_str("alternate does not follow the item it replaces")
+            }
+            MenuDiagnosticKind::EmptyLabel => f.write_str("empty label"),
+            MenuDiagnosticKind::EmptySubmenu => f.write_str("submenu has no items"),
+            MenuDiagnosticKind::RedundantSeparator => f.write_str("redundant separator"),
+            MenuDiagnosticKind::CommandIdOutOfRange { limit } => {
+                write!(f, "command id is larger than {limit}")
+            }
+            MenuDiagnosticKind::UnrepresentableShortcut(sequence) => {
+                write!(
+                    f,
+                    "shortcut `{sequence}` cannot be installed on this platform"
+                )
+            }
+        }
+    }
+}
+
+/// The diagnostics of one model, in tree order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MenuValidation {
+    pub diagnostics: Vec<MenuDiagnostic>,
+}
+
+impl MenuValidation {
+    /// No errors; warnings are allowed.
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &MenuDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &MenuDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity() == Severity::Warning)
+    }
+}
+
+impl MenuBarModel {
+    /// `validate_for` the platform this crate was compiled for.
+    pub fn validate(&self) -> MenuValidation {
+        self.validate_for(Platform::current())
+    }
+
+    pub fn validate_for(&self, platform: Platform) -> MenuValidation {
+        let mut validator = Validator::new(platform);
+        let mut top_roles: HashMap<TopMenuRole, MenuPath> = HashMap::new();
+        for (index, menu) in self.menus.iter().enumerate() {
+            let path = MenuPath::root().child(index, &menu.label);
+            // macOS titles the app menu with the app name, whatever the label.
+            if menu.label.is_empty() && menu.role != Some(TopMenuRole::App) {
+                validator.push(MenuDiagnosticKind::EmptyLabel, &path);
+            }
+            if let Some(role) = menu.role {
+                match top_roles.get(&role) {
+                    Some(first) => validator.push(
+                        MenuDiagnosticKind::DuplicateRole {
+                            first: first.clone(),
+                        },
+                        &path,
+                    ),
+                    None => {
+                        top_roles.insert(role, path.clone());
+                    }
+                }
+            }
+            if menu.items.is_empty() {
+                validator.push(MenuDiagnosticKind::EmptySubmenu, &path);
+            }
+            validator.check_items(&path, &menu.items);
+        }
+        validator.finish()
+    }
+}
+
+impl MenuModel {
+    /// `validate_for` the platform this crate was compiled for.
+    pub fn validate(&self) -> MenuValidation {
+        self.validate_for(Platform::current())
+    }
+
+    pub fn validate_for(&self, platform: Platform) -> MenuValidation {
+        let mut validator = Validator::new(platform);
+        validator.check_items(&MenuPath::root(), &self.items);
+        validator.finish()
+    }
+}
+
+impl TrayModel {
+    /// `validate_for` the platform this crate was compiled for.
+    pub fn validate(&self) -> MenuValidation {
+        self.validate_for(Platform::current())
+    }
+
+    pub fn validate_for(&self, platform: Platform) -> MenuValidation {
+        let mut validator = Validator::new(platform);
+        validator.check_items(&MenuPath::root(), &self.menu.items);
+        validator.finish()
+    }
+}
+
+/// Largest command id the platform backend can map to a native item id.
+fn command_id_limit(platform: Platform) -> u64 {
+    match platform {
+        // NSMenuItem tags are NSInteger.
+        Platform::MacOs => isize::MAX as u64,
+        // Menu item ids are UINT.
+        Platform::Windows => u32::MAX as u64,
+        Platform::Linux => u64::MAX,
+    }
+}
+
+fn shortcut_representable(platform: Platform, sequence: ShortcutSequence) -> bool {
+    match platform {
+        // Key equivalents are single strokes and have no media keys.
+        Platform::MacOs => sequence
+            .as_single()
+            .is_some_and(|shortcut| !matches!(shortcut.key, Key::Media(_))),
+        // Win-key combinations are reserved by the shell.
+        Platform::Windows => sequence.strokes().iter().all(|stroke| !stroke.mods.meta),
+        Platform::Linux => true,
+    }
+}
+
+struct Validator {
+    platform: Platform,
+    ids: HashMap<CommandId, MenuPath>,
+    roles: HashMap<MenuItemRole, MenuPath>,
+    diagnostics: Vec<MenuDiagnostic>,
+}
+
+impl Validator {
+    fn new(platform: Platform) -> Self {
+        Self {
+            platform,
+            ids: HashMap::new(),
+            roles: HashMap::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn finish(self) -> MenuValidation {
+        MenuValidation {
+            diagnostics: self.diagnostics,
+        }
+    }
+
+    fn push(&mut self, kind: MenuDiagnosticKind, path: &MenuPath) {
+        self.diagnostics.push(MenuDiagnostic {
+            kind,
+            path: path.clone(),
+        });
+    }
+
+    fn check_items(&mut self, parent: &MenuPath, items: &[MenuItem]) {
+        let mut keys: HashMap<&str, MenuPath> = HashMap::new();
+        let mut radio_on: HashMap<&str, MenuPath> = HashMap::new();
+        for (index, item) in items.iter().enumerate() {
+            if let Some(key) = item.key() {
+                let label = match item {
+                    MenuItem::Command(cmd) => cmd.label.as_str(),
+                    MenuItem::Submenu(sub) => sub.label.as_str(),
+                    MenuItem::Separator(_) => "",
+                };
+                let path = parent.child(index, label);
+                match keys.get(key) {
+                    Some(first) => {
+                        let kind = MenuDiagnosticKind::DuplicateKey {
+                            first: first.clone(),
+                        };
+                        self.push(kind, &path);
+                    }
+                    None => {
+                        keys.insert(key, path);
+                    }
+                }
+            }
+            match item {
+                MenuItem::Command(cmd) => {
+                    let path = parent.child(index, &cmd.label);
+                    if cmd.label.is_empty() {
+                        self.push(MenuDiagnosticKind::EmptyLabel, &path);
+                    }
+                    match self.ids.get(&cmd.id) {
+                        Some(first) => {
+                            let kind = MenuDiagnosticKind::DuplicateCommandId {
+                                first: first.clone(),
+                            };
+                            self.push(kind, &path);
+                        }
+                        None => {
+                            self.ids.insert(cmd.id, path.clone());
+                        }
+                    }
+                    if let Some(base) = cmd.alternate_of {
+                        let previous = index.checked_sub(1).and_then(|prev| items.get(prev));
+                        if !matches!(previous, Some(MenuItem::Command(prev)) if prev.id == base) {
+                            self.push(MenuDiagnosticKind::DetachedAlternate, &path);
+                        }
+                    }
+                    if let Some(group) = cmd.radio_group.as_deref()
+                        && cmd.checked.is_on()
+                    {
+                        match radio_on.get(group) {
+                            Some(first) => {
+                                let kind = MenuDiagnosticKind::RadioGroupConflict {
+                                    first: first.clone(),
+                                };
+                                self.push(kind, &path);
+                            }
+                            None => {
+                                radio_on.insert(group, path.clone());
+                            }
+                        }
+                    }
+                    if let Some(role) = cmd.role {
+                        match self.roles.get(&role) {
+                            Some(first) => {
+                                let kind = MenuDiagnosticKind::DuplicateRole {
+                                    first: first.clone(),
+                                };
+                                self.push(kind, &path);
+                            }
+                            None => {
+                                self.roles.insert(role, path.clone());
+                            }
+                        }
+                    }
+                    let limit = command_id_limit(self.platform);
+                    if cmd.id.as_u64() > limit {
+                        self.push(MenuDiagnosticKind::CommandIdOutOfRange { limit }, &path);
+                    }
+                    if let Some(sequence) = cmd.shortcut
+                        && !shortcut_representable(self.platform, sequence)
+                    {
+                        let kind = MenuDiagnosticKind::UnrepresentableShortcut(sequence);
+                        self.push(kind, &path);
+                    }
+                }
+                MenuItem::Submenu(sub) => {
+                    let path = parent.child(index, &sub.label);
+                    if sub.label.is_empty() {
+                        self.push(MenuDiagnosticKind::EmptyLabel, &path);
+                    }
+                    if sub.items.is_empty() && sub.provider.is_none() {
+                        self.push(MenuDiagnosticKind::EmptySubmenu, &path);
+                    }
+                    self.check_items(&path, &sub.items);
+                }
+                MenuItem::Separator(_) => {
+                    let first = index == 0;
+                    let last = index + 1 == items.len();
+                    let repeated = matches!(items.get(index + 1), Some(MenuItem::Separator(_)));
+                    if first || last || repeated {
+                        self.push(
+                            MenuDiagnosticKind::RedundantSeparator,
+                            &parent.child(index, ""),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::test_items::{command, id};
+    use crate::menu::{CheckState, SeparatorItem, Submenu, TopMenu};
+
+    fn kinds(items: Vec<MenuItem>, platform: Platform) -> Vec<MenuDiagnosticKind> {
+        MenuModel::new(items)
+            .validate_for(platform)
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect()
+    }
+
+    fn root(index: usize, label: &str) -> MenuPath {
+        MenuPath::root().child(index, label)
+    }
+
+    #[test]
+    fn clean_menu_has_no_diagnostics() {
+        let items = vec![
+            MenuItem::Command(command(1, "Open")),
+            MenuItem::separator(),
+            MenuItem::Submenu(Submenu::new(
+                "Recent",
+                vec![MenuItem::Command(command(2, "a"))],
+            )),
+        ];
+        assert!(kinds(items, Platform::Windows).is_empty());
+    }
+
+    #[test]
+    fn duplicate_command_id() {
+        let items = vec![
+            MenuItem::Command(command(1, "Open")),
+            MenuItem::Submenu(Submenu::new(
+                "More",
+                vec![MenuItem::Command(command(1, "Again"))],
+            )),
+        ];
+        let validation = MenuModel::new(items).validate_for(Platform::Linux);
+        assert_eq!(
+            validation.diagnostics,
+            vec![MenuDiagnostic {
+                kind: MenuDiagnosticKind::DuplicateCommandId {
+                    first: root(0, "Open"),
+                },
+                path: root(1, "More").child(0, "Again"),
+            }]
+        );
+        assert_eq!(
+            validation.diagnostics[0].to_string(),
+            "warning: More > Again: command id already used by Open"
+        );
+    }
+
+    #[test]
+    fn duplicate_role() {
+        let items = vec![
+            MenuItem::Command(command(1, "Quit").with_role(MenuItemRole::Quit)),
+            MenuItem::Command(command(2, "Exit").with_role(MenuItemRole::Quit)),
+        ];
+        assert_eq!(
+            kinds(items, Platform::MacOs),
+            vec![MenuDiagnosticKind::DuplicateRole {
+                first: root(0, "Quit"),
+            }]
+        );
+
+        let bar = MenuBarModel::new(vec![
+            TopMenu::new("Edit", vec![MenuItem::Command(command(1, "Copy"))])
+                .with_role(TopMenuRole::Edit),
+            TopMenu::new("Edit", vec![MenuItem::Command(command(2, "Paste"))])
+                .with_role(TopMenuRole::Edit),
+        ]);
+        let diagnostics = bar.validate_for(Platform::MacOs).diagnostics;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, root(1, "Edit"));
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let items = vec![
+            MenuItem::Command(command(1, "a.txt").with_key("a.txt")),
+            MenuItem::Command(command(2, "a.txt").with_key("a.txt")),
+        ];
+        assert_eq!(
+            kinds(items, Platform::Linux),
+            vec![MenuDiagnosticKind::DuplicateKey {
+                first: root(0, "a.txt"),
+            }]
+        );
+    }
+
+    #[test]
+    fn radio_group_conflict() {
+        let mut small = command(1, "Small").with_radio_group("size");
+        let mut large = command(2, "Large").with_radio_group("size");
+        small.checked = CheckState::On;
+        large.checked = CheckState::On;
+        let mut other = command(3, "Other").with_radio_group("other");
+        other.checked = CheckState::On;
+        let items = vec![
+            MenuItem::Command(small),
+            MenuItem::Command(large),
+            MenuItem::Command(other),
+        ];
+        assert_eq!(
+            kinds(items, Platform::Linux),
+            vec![MenuDiagnosticKind::RadioGroupConflict {
+                first: root(0, "Small"),
+            }]
+        );
+    }
+
+    #[test]
+    fn detached_alternate() {
+        let attached = vec![
+            MenuItem::Command(command(1, "Close")),
+            MenuItem::Command(command(2, "Close All").with_alternate_of(id(1))),
+        ];
+        assert!(kinds(attached, Platform::MacOs).is_empty());
+
+        let detached = vec![
+            MenuItem::Command(command(1, "Close")),
+            MenuItem::separator(),
+            MenuItem::Command(command(2, "Close All").with_alternate_of(id(1))),
+        ];
+        assert_eq!(
+            kinds(detached, Platform::MacOs),
+            vec![MenuDiagnosticKind::DetachedAlternate]
+        );
+    }
+
+    #[test]
+    fn empty_label() {
+        let items = vec![MenuItem::Command(command(1, ""))];
+        assert_eq!(
+            kinds(items, Platform::Linux),
+            vec![MenuDiagnosticKind::EmptyLabel]
+        );
+
+        // The app menu is titled by the system.
+        let bar = MenuBarModel::new(vec![
+            TopMenu::new("", vec![MenuItem::Command(command(1, "About"))])
+                .with_role(TopMenuRole::App),
+        ]);
+        assert!(bar.validate_for(Platform::MacOs).diagnostics.is_empty());
+    }
+
+    #[test]
+    fn empty_submenu() {
+        let items = vec![MenuItem::Submenu(Submenu::new("Recent", Vec::new()))];
+        assert_eq!(
+            kinds(items, Platform::Linux),
+            vec![MenuDiagnosticKind::EmptySubmenu]
+        );
+
+        let bar = MenuBarModel::new(vec![TopMenu::new("File", Vec::new())]);
+        let diagnostics = bar.validate_for(Platform::Linux).diagnostics;
+        assert_eq!(diagnostics[0].kind, MenuDiagnosticKind::EmptySubmenu);
+        assert_eq!(diagnostics[0].path, root(0, "File"));
+    }
+
+    #[test]
+    fn redundant_separators() {
+        let items = vec![
+            MenuItem::separator(),
+            MenuItem::Command(command(1, "Open")),
+            MenuItem::separator(),
+            MenuItem::Separator(SeparatorItem::with_key("gap")),
+            MenuItem::Command(command(2, "Quit")),
+            MenuItem::separator(),
+        ];
+        let paths: Vec<_> = MenuModel::new(items)
+            .validate_for(Platform::Linux)
+            .diagnostics
+            .into_iter()
+            .inspect(|diagnostic| {
+                assert_eq!(diagnostic.kind, MenuDiagnosticKind::RedundantSeparator)
+            })
+            .map(|diagnostic| diagnostic.path.to_string())
+            .collect();
+        assert_eq!(paths, ["#0", "#2", "#5"]);
+    }
+
+    #[test]
+    fn command_id_out_of_range_is_an_error() {
+        let items = vec![MenuItem::Command(command(u32::MAX as u64 + 1, "Big"))];
+        let validation = MenuModel::new(items.clone()).validate_for(Platform::Windows);
+        assert_eq!(
+            validation.diagnostics[0].kind,
+            MenuDiagnosticKind::CommandIdOutOfRange {
+                limit: u32::MAX as u64,
+            }
+        );
+        assert!(!validation.is_ok());
+        assert_eq!(validation.errors().count(), 1);
+        assert!(MenuModel::new(items).validate_for(Platform::MacOs).is_ok());
+    }
+
+    #[test]
+    fn unrepresentable_shortcut() {
+        let chord: ShortcutSequence = "Ctrl+K Ctrl+S".parse().unwrap();
+        let win: ShortcutSequence = "Meta+E".parse().unwrap();
+        let items = vec![
+            MenuItem::Command(command(1, "Save").with_shortcut(chord)),
+            MenuItem::Command(command(2, "Explore").with_shortcut(win)),
+        ];
+        assert_eq!(
+            kinds(items.clone(), Platform::MacOs),
+            vec![MenuDiagnosticKind::UnrepresentableShortcut(chord)]
+        );
+        assert_eq!(
+            kinds(items.clone(), Platform::Windows),
+            vec![MenuDiagnosticKind::UnrepresentableShortcut(win)]
+        );
+        let validation = MenuModel::new(items).validate_for(Platform::Windows);
+        assert!(validation.is_ok());
+        assert_eq!(validation.warnings().count(), 1);
+    }
+}
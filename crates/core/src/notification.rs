@@ -1,6 +1,7 @@
 use crate::command::CommandId;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Notification {
     pub title: String,
     pub body: Option<String>,
@@ -56,6 +57,7 @@ impl Notification {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NotificationButton {
     pub label: String,
     pub command: CommandId,
@@ -71,8 +73,50 @@ impl NotificationButton {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum NotificationSound {
     Default,
     None,
     Custom(String),
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notification_matches_golden_json() {
+        let id = |n| CommandId::new(n).unwrap();
+        let notification = Notification::new("Done")
+            .with_body("Export finished")
+            .with_default_action(id(7))
+            .with_action_button(NotificationButton::new(id(8), "Open"))
+            .with_sound(NotificationSound::Custom("ping.aiff".to_string()));
+        let golden = serde_json::json!({
+            "title": "Done",
+            "body": "Export finished",
+            "subtitle": null,
+            "identifier": null,
+            "default_action": 7,
+            "action_button": {"label": "Open", "command": 8},
+            "sound": {"custom": "ping.aiff"},
+        });
+        assert_eq!(serde_json::to_value(&notification).unwrap(), golden);
+
+        let parsed: Notification = serde_json::from_value(golden.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), golden);
+    }
+
+    #[test]
+    fn plain_sounds_are_snake_case_names() {
+        assert_eq!(
+            serde_json::to_value(&NotificationSound::Default).unwrap(),
+            "default"
+        );
+        assert!(matches!(
+            serde_json::from_str(r#""none""#).unwrap(),
+            NotificationSound::None
+        ));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::command::CommandId;
+use crate::menu::{MenuBarModel, MenuItem, MenuModel, MenuPath};
+use crate::platform::Platform;
+use crate::tray::TrayMenuModel;
+
+use super::ShortcutSequence;
+
+/// One command item's effective binding, as seen by the conflict analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingSite {
+    /// Name given to the model when it was added, e.g. `"app-menu"`.
+    pub source: String,
+    pub path: MenuPath,
+    pub command: CommandId,
+    pub sequence: ShortcutSequence,
+    /// The binding comes from the item's role default, not an explicit shortcut.
+    pub role_default: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Both sites bind the same sequence to different commands.
+    Duplicate,
+    /// `second` binds the sequence `first` gets from its role default.
+    ShadowsRoleDefault,
+    /// `first`'s sequence is a strict prefix of `second`'s chord.
+    PrefixOfChord,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortcutConflict {
+    pub kind: ConflictKind,
+    pub first: BindingSite,
+    pub second: BindingSite,
+}
+
+/// Collects bindings from any number of menu models and reports the ones that
+/// cannot all work at once.
+///
+/// Role defaults (`MenuItemRole::default_shortcut`) count as bindings only
+/// for macOS, the one backend that installs them. Disabled items are
+/// included: enabling them later would surface the conflict.
+#[derive(Debug, Clone)]
+pub struct ShortcutConflicts {
+    platform: Platform,
+    sites: Vec<BindingSite>,
+}
+
+impl Default for ShortcutConflicts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShortcutConflicts {
+    /// `for_platform` the platform this crate was compiled for.
+    pub fn new() -> Self {
+        Self::for_platform(Platform::current())
+    }
+
+    pub fn for_platform(platform: Platform) -> Self {
+        Self {
+            platform,
+            sites: Vec::new(),
+        }
+    }
+
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    pub fn add_menu_bar(&mut self, source: &str, model: &MenuBarModel) -> &mut Self {
+        for (index, menu) in model.menus.iter().enumerate() {
+            let path = MenuPath::root().child(index, &menu.label);
+            self.add_menu_items(source, &path, &menu.items);
+        }
+        self
+    }
+
+    pub fn add_menu(&mut self, source: &str, model: &MenuModel) -> &mut Self {
+        self.add_menu_items(source, &MenuPath::root(), &model.items);
+        self
+    }
+
+    pub fn add_tray_menu(&mut self, source: &str, model: &TrayMenuModel) -> &mut Self {
+        self.add_menu_items(source, &MenuPath::root(), &model.items);
+        self
+    }
+
+    pub fn sites(&self) -> &[BindingSite] {
+        &self.sites
+    }
+
+    /// Every conflicting pair, in the order the sites were added. A sequence
+    /// bound by several commands is reported against its first binding.
+    pub fn conflicts(&self) -> Vec<ShortcutConflict> {
+        let mut conflicts = Vec::new();
+        for (index, a) in self.sites.iter().enumerate() {
+            for b in &self.sites[index + 1..] {
+                if a.command == b.command {
+                    continue;
+                }
+                let (a_seq, b_seq) = (a.sequence.normalized(), b.sequence.normalized());
+                let (kind, first, second) = if a_seq == b_seq {
+                    if self.bound_earlier(index, a_seq) {
+                        continue;
+                    }
+                    match (a.role_default, b.role_default) {
+                        (true, false) => (ConflictKind::ShadowsRoleDefault, a, b),
+                        (false, true) => (ConflictKind::ShadowsRoleDefault, b, a),
+                        _ => (ConflictKind::Duplicate, a, b),
+                    }
+                } else if is_strict_prefix(a_seq, b_seq) {
+                    (ConflictKind::PrefixOfChord, a, b)
+                } else if is_strict_prefix(b_seq, a_seq) {
+                    (ConflictKind::PrefixOfChord, b, a)
+                } else {
+                    continue;
+                };
+                conflicts.push(ShortcutConflict {
+                    kind,
+                    first: first.clone(),
+                    second: second.clone(),
+                });
+            }
+        }
+        conflicts
+    }
+
+    fn bound_earlier(&self, index: usize, sequence: ShortcutSequence) -> bool {
+        self.sites[..index]
+            .iter()
+            .any(|site| site.sequence.normalized() == sequence)
+    }
+
+    fn push_site(
+        &mut self,
+        source: &str,
+        path: MenuPath,
+        command: CommandId,
+        shortcut: Option<ShortcutSequence>,
+        role_default: Option<ShortcutSequence>,
+    ) {
+        let (sequence, role_default) = match (shortcut, role_default) {
+            (Some(sequence), _) => (sequence, false),
+            (None, Some(sequence)) => (sequence, true),
+            (None, None) => return,
+        };
+        self.sites.push(BindingSite {
+            source: source.to_string(),
+            path,
+            command,
+            sequence,
+            role_default,
+        });
+    }
+
+    fn add_menu_items(&mut self, source: &str, parent: &MenuPath, items: &[MenuItem]) {
+        for (index, item) in items.iter().enumerate() {
+            match item {
+                MenuItem::Command(cmd) => {
+                    let role_default = cmd
+                        .role
+                        .filter(|_| cmd.role_shortcut && self.platform == Platform::MacOs)
+                        .and_then(|role| role.default_shortcut());
+                    self.push_site(
+                        source,
+                        parent.child(index, &cmd.label),
+                        cmd.id,
+                        cmd.shortcut,
+                        role_default.map(ShortcutSequence::from),
+                    );
+                }
+                MenuItem::Submenu(sub) => {
+                    self.add_menu_items(source, &parent.child(index, &sub.label), &sub.items);
+                }
+                MenuItem::Separator(_) => {}
+            }
+        }
+    }
+}
+
+fn is_strict_prefix(prefix: ShortcutSequence, sequence: ShortcutSequence) -> bool {
+    prefix.len() < sequence.len() && sequence.starts_with(prefix.strokes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::test_items::{bound, id};
+    use crate::menu::{CommandItem, MenuItemRole, Submenu, TopMenu};
+
+    fn quit() -> MenuItem {
+        MenuItem::Command(CommandItem::new(id(9), "Quit").with_role(MenuItemRole::Quit))
+    }
+
+    #[test]
+    fn reports_duplicate_across_commands() {
+        let mut conflicts = ShortcutConflicts::for_platform(Platform::Linux);
+        conflicts
+            .add_menu_bar(
+                "app-menu",
+                &MenuBarModel::new(vec![TopMenu::new(
+                    "File",
+                    vec![bound(1, "Save", "Ctrl+S"), bound(2, "Share", "ctrl+s")],
+                )]),
+            )
+            .add_menu("context", &MenuModel::new(vec![bound(3, "Sync", "Ctrl+S")]));
+
+        let found = conflicts.conflicts();
+        assert_eq!(found.len(), 2);
+        for (conflict, second) in found.iter().zip([2, 3]) {
+            assert_eq!(conflict.kind, ConflictKind::Duplicate);
+            assert_eq!(conflict.first.command, id(1));
+            assert_eq!(conflict.second.command, id(second));
+        }
+        assert_eq!(found[1].second.source, "context");
+    }
+
+    #[test]
+    fn same_command_twice_is_not_a_conflict() {
+        let mut conflicts = ShortcutConflicts::for_platform(Platform::Windows);
+        conflicts.add_menu(
+            "context",
+            &MenuModel::new(vec![bound(1, "Save", "Ctrl+S"), bound(1, "Save", "Ctrl+S")]),
+        );
+        assert!(conflicts.conflicts().is_empty());
+    }
+
+    #[test]
+    fn shadowing_a_role_default_counts_only_on_macos() {
+        let model = MenuModel::new(vec![quit(), bound(1, "Quick Open", "Cmd+Q")]);
+
+        let mut mac = ShortcutConflicts::for_platform(Platform::MacOs);
+        mac.add_menu("app-menu", &model);
+        let found = mac.conflicts();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, ConflictKind::ShadowsRoleDefault);
+        assert_eq!(
+            (found[0].first.command, found[0].second.command),
+            (id(9), id(1))
+        );
+        assert!(found[0].first.role_default);
+        assert!(!found[0].second.role_default);
+
+        for platform in [Platform::Windows, Platform::Linux] {
+            let mut other = ShortcutConflicts::for_platform(platform);
+            other.add_menu("app-menu", &model);
+            assert_eq!(other.sites().len(), 1);
+            assert!(other.conflicts().is_empty());
+        }
+    }
+
+    #[test]
+    fn role_default_turned_off_is_not_a_binding() {
+        let mut quit = CommandItem::new(id(9), "Quit").with_role(MenuItemRole::Quit);
+        quit.role_shortcut = false;
+        let mut conflicts = ShortcutConflicts::for_platform(Platform::MacOs);
+        conflicts.add_menu(
+            "app-menu",
+            &MenuModel::new(vec![
+                MenuItem::Command(quit),
+                bound(1, "Quick Open", "Cmd+Q"),
+            ]),
+        );
+        assert!(conflicts.conflicts().is_empty());
+    }
+
+    #[test]
+    fn reports_prefix_of_chord() {
+        let mut conflicts = ShortcutConflicts::for_platform(Platform::Linux);
+        conflicts.add_menu(
+            "context",
+            &MenuModel::new(vec![
+                bound(1, "Save All", "Ctrl+K Ctrl+S"),
+                bound(2, "Clear", "Ctrl+K"),
+            ]),
+        );
+        let found = conflicts.conflicts();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, ConflictKind::PrefixOfChord);
+        assert_eq!(
+            (found[0].first.command, found[0].second.command),
+            (id(2), id(1))
+        );
+    }
+
+    #[test]
+    fn reports_item_paths() {
+        let mut conflicts = ShortcutConflicts::for_platform(Platform::Linux);
+        conflicts
+            .add_menu_bar(
+                "app-menu",
+                &MenuBarModel::new(vec![
+                    TopMenu::new("File", vec![]),
+                    TopMenu::new(
+                        "Edit",
+                        vec![
+                            MenuItem::separator(),
+                            MenuItem::Submenu(Submenu::new(
+                                "Find",
+                                vec![bound(1, "Find Next", "Ctrl+G")],
+                            )),
+                        ],
+                    ),
+                ]),
+            )
+            .add_tray_menu(
+                "tray",
+                &TrayMenuModel::new(vec![bound(2, "Go To", "Ctrl+G")]),
+            );
+
+        let found = conflicts.conflicts();
+        assert_eq!(found.len(), 1);
+        let (first, second) = (&found[0].first, &found[0].second);
+        assert_eq!(first.path.indices, [1, 1, 0]);
+        assert_eq!(first.path.to_string(), "Edit > Find > Find Next");
+        assert_eq!(
+            (second.source.as_str(), second.path.indices.as_slice()),
+            ("tray", &[0][..])
+        );
+        assert_eq!(second.path.to_string(), "Go To");
+    }
+}
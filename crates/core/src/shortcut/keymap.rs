@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::command::CommandId;
+use crate::menu::{MenuBarModel, MenuItem, MenuModel};
+use crate::tray::TrayMenuModel;
+
+use super::{ParseShortcutError, ShortcutSequence};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyBinding {
+    Bound(ShortcutSequence),
+    /// No shortcut, not even the role default.
+    Unbound,
+}
+
+/// User overrides of the shortcuts declared in menu models.
+///
+/// The models an app builds stay the defaults: `apply_*` returns a rewritten
+/// copy, so "reset to default" is `reset` (or `clear`) followed by applying
+/// the keymap to the original model again. Commands without an override keep
+/// their declared shortcut.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Keymap {
+    overrides: HashMap<CommandId, KeyBinding>,
+}
+
+impl Keymap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(&mut self, command: CommandId, sequence: impl Into<ShortcutSequence>) {
+        self.overrides
+            .insert(command, KeyBinding::Bound(sequence.into()));
+    }
+
+    pub fn unbind(&mut self, command: CommandId) {
+        self.overrides.insert(command, KeyBinding::Unbound);
+    }
+
+    /// Drops the override for `command`, returning it.
+    pub fn reset(&mut self, command: CommandId) -> Option<KeyBinding> {
+        self.overrides.remove(&command)
+    }
+
+    pub fn clear(&mut self) {
+        self.overrides.clear();
+    }
+
+    pub fn get(&self, command: CommandId) -> Option<KeyBinding> {
+        self.overrides.get(&command).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (CommandId, KeyBinding)> + '_ {
+        self.overrides.iter().map(|(id, binding)| (*id, *binding))
+    }
+
+    pub fn len(&self) -> usize {
+        self.overrides.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    pub fn apply_menu_bar(&self, model: &MenuBarModel) -> MenuBarModel {
+        let mut model = model.clone();
+        for menu in &mut model.menus {
+            self.apply_menu_items(&mut menu.items);
+        }
+        model
+    }
+
+    pub fn apply_menu(&self, model: &MenuModel) -> MenuModel {
+        let mut model = model.clone();
+        self.apply_menu_items(&mut model.items);
+        model
+    }
+
+    pub fn apply_tray_menu(&self, model: &TrayMenuModel) -> TrayMenuModel {
+        let mut model = model.clone();
+        self.apply_menu_items(&mut model.items);
+        model
+    }
+
+    /// Parses a keymap file, one binding per line:
+    ///
+    /// ```text
+    /// # comment
+    /// file.save   = CmdOrCtrl+S
+    /// edit.format = Ctrl+K Ctrl+F
+    /// view.grid   = unbound
+    /// ```
+    ///
+    /// Command names are mapped to ids by `resolve`; the right-hand side uses
+    /// the `ShortcutSequence` grammar. A later line for the same command wins.
+    pub fn from_config(
+        text: &str,
+        mut resolve: impl FnMut(&str) -> Option<CommandId>,
+    ) -> Result<Self, KeymapError> {
+        let mut keymap = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_no = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, binding)) = line.split_once('=') else {
+                return Err(KeymapError::new(
+                    KeymapErrorKind::MissingSeparator,
+                    line,
+                    line_no,
+                ));
+            };
+            let (name, binding) = (name.trim(), binding.trim());
+            let command = resolve(name)
+                .ok_or_else(|| KeymapError::new(KeymapErrorKind::UnknownCommand, name, line_no))?;
+            if binding.eq_ignore_ascii_case("unbound") {
+                keymap.unbind(command);
+            } else {
+                let sequence = binding.parse::<ShortcutSequence>().map_err(|err| {
+                    KeymapError::new(KeymapErrorKind::InvalidShortcut(err), binding, line_no)
+                })?;
+                keymap.bind(command, sequence);
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Writes the overrides in the `from_config` format, sorted by command
+    /// name. Commands `name` returns `None` for are skipped.
+    pub fn to_config<'a>(&self, mut name: impl FnMut(CommandId) -> Option<&'a str>) -> String {
+        let mut lines: Vec<_> = self
+            .iter()
+            .filter_map(|(id, binding)| {
+                let binding = match binding {
+                    KeyBinding::Bound(sequence) => sequence.to_string(),
+                    KeyBinding::Unbound => "unbound".to_string(),
+                };
+                name(id).map(|name| format!("{name} = {binding}\n"))
+            })
+            .collect();
+        lines.sort();
+        lines.concat()
+    }
+
+    fn apply_menu_items(&self, items: &mut [MenuItem]) {
+        for item in items {
+            match item {
+                MenuItem::Command(cmd) => match self.get(cmd.id) {
+                    Some(KeyBinding::Bound(sequence)) => cmd.shortcut = Some(sequence),
+                    Some(KeyBinding::Unbound) => {
+                        cmd.shortcut = None;
+                        cmd.role_shortcut = false;
+                    }
+                    None => {}
+                },
+                MenuItem::Submenu(sub) => self.apply_menu_items(&mut sub.items),
+                MenuItem::Separator(_) => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeymapError {
+    kind: KeymapErrorKind,
+    token: String,
+    line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapErrorKind {
+    /// The line has no `=`.
+    MissingSeparator,
+    UnknownCommand,
+    InvalidShortcut(ParseShortcutError),
+}
+
+impl KeymapError {
+    fn new(kind: KeymapErrorKind, token: &str, line: usize) -> Self {
+        Self {
+            kind,
+            token: token.to_string(),
+            line,
+        }
+    }
+
+    pub fn kind(&self) -> &KeymapErrorKind {
+        &self.kind
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// 1-based line number in the config text.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            KeymapErrorKind::MissingSeparator => {
+                write!(f, "line {}: expected `command = shortcut`", self.line)
+            }
+            KeymapErrorKind::UnknownCommand => {
+                write!(f, "line {}: unknown command `{}`", self.line, self.token)
+            }
+            KeymapErrorKind::InvalidShortcut(err) => {
+                write!(
+                    f,
+                    "line {}: invalid shortcut `{}`: {err}",
+                    self.line, self.token
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            KeymapErrorKind::InvalidShortcut(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::test_items::{find_command, id, seq};
+    use crate::menu::{CommandItem, MenuItemRole, Submenu};
+
+    fn menu() -> MenuModel {
+        MenuModel::new(vec![
+            MenuItem::Command(CommandItem::new(id(1), "Save").with_shortcut(seq("Ctrl+S"))),
+            MenuItem::Submenu(Submenu::new(
+                "App",
+                vec![MenuItem::Command(
+                    CommandItem::new(id(2), "Quit").with_role(MenuItemRole::Quit),
+                )],
+            )),
+        ])
+    }
+
+    #[test]
+    fn override_replaces_declared_shortcut() {
+        let mut keymap = Keymap::new();
+        keymap.bind(id(1), seq("Ctrl+K Ctrl+S"));
+        let applied = keymap.apply_menu(&menu());
+        assert_eq!(
+            find_command(&applied.items, 1).shortcut,
+            Some(seq("Ctrl+K Ctrl+S"))
+        );
+        // Commands without an override keep their role default.
+        assert!(find_command(&applied.items, 2).role_shortcut);
+
+        assert_eq!(
+            keymap.reset(id(1)),
+            Some(KeyBinding::Bound(seq("Ctrl+K Ctrl+S")))
+        );
+        assert_eq!(keymap.apply_menu(&menu()), menu());
+    }
+
+    #[test]
+    fn unbind_clears_shortcut_and_role_default() {
+        let mut keymap = Keymap::new();
+        keymap.unbind(id(1));
+        keymap.unbind(id(2));
+        let applied = keymap.apply_menu(&menu());
+        assert_eq!(find_command(&applied.items, 1).shortcut, None);
+        let quit = find_command(&applied.items, 2);
+        assert_eq!(quit.shortcut, None);
+        assert!(!quit.role_shortcut);
+        assert_eq!(keymap.get(id(2)), Some(KeyBinding::Unbound));
+    }
+
+    #[test]
+    fn config_round_trips() {
+        let names = [("file.save", id(1)), ("app.quit", id(2))];
+        let resolve = |name: &str| names.iter().find(|(n, _)| *n == name).map(|(_, id)| *id);
+        let name = |command| names.iter().find(|(_, id)| *id == command).map(|(n, _)| *n);
+
+        let keymap = Keymap::from_config(
+            "# overrides\nfile.save = Ctrl+S\n\napp.quit=unbound\nfile.save = Ctrl+K Ctrl+S\n",
+            resolve,
+        )
+        .unwrap();
+        assert_eq!(keymap.len(), 2);
+        assert_eq!(
+            keymap.get(id(1)),
+            Some(KeyBinding::Bound(seq("Ctrl+K Ctrl+S")))
+        );
+
+        let text = keymap.to_config(name);
+        assert_eq!(text, "app.quit = unbound\nfile.save = Ctrl+K Ctrl+S\n");
+        assert_eq!(Keymap::from_config(&text, resolve).unwrap(), keymap);
+    }
+
+    #[test]
+    fn config_errors_report_line() {
+        let resolve = |name: &str| (name == "file.save").then(|| id(1));
+
+        let err = Keymap::from_config("\nfile.save Ctrl+S", resolve).unwrap_err();
+        assert_eq!(
+            (err.kind(), err.line()),
+            (&KeymapErrorKind::MissingSeparator, 2)
+        );
+
+        let err = Keymap::from_config("file.open = Ctrl+O", resolve).unwrap_err();
+        assert_eq!(
+            (err.kind(), err.token()),
+            (&KeymapErrorKind::UnknownCommand, "file.open")
+        );
+
+        let err = Keymap::from_config("file.save = Ctrl+Nope", resolve).unwrap_err();
+        assert!(matches!(err.kind(), KeymapErrorKind::InvalidShortcut(_)));
+        assert_eq!(err.token(), "Ctrl+Nope");
+    }
+}
@@ -0,0 +1,185 @@
+use crate::platform::Platform;
+
+use super::{Key, MediaKey, NumpadKey, Shortcut};
+
+impl Shortcut {
+    /// Renders the shortcut the way the platform's own menus show it:
+    /// `⌃⌥⇧⌘S` on macOS, `Ctrl+Alt+Shift+S` on Windows and `Ctrl+Alt+Shift+Super+S` on Linux.
+    pub fn label_for(&self, platform: Platform) -> String {
+        match platform {
+            Platform::MacOs => {
+                let mut label = String::new();
+                if self.mods.ctrl {
+                    label.push('⌃');
+                }
+                if self.mods.alt {
+                    label.push('⌥');
+                }
+                if self.mods.shift {
+                    label.push('⇧');
+                }
+                if self.mods.meta {
+                    label.push('⌘');
+                }
+                label.push_str(&key_label(self.key, platform));
+                label
+            }
+            Platform::Windows | Platform::Linux => {
+                let mut parts: Vec<String> = Vec::new();
+                if self.mods.ctrl {
+                    parts.push("Ctrl".to_string());
+                }
+                if self.mods.alt {
+                    parts.push("Alt".to_string());
+                }
+                if self.mods.shift {
+                    parts.push("Shift".to_string());
+                }
+                if self.mods.meta {
+                    let name = if platform == Platform::Windows {
+                        "Win"
+                    } else {
+                        "Super"
+                    };
+                    parts.push(name.to_string());
+                }
+                parts.push(key_label(self.key, platform));
+                parts.join("+")
+            }
+        }
+    }
+
+    /// `label_for` the platform this crate was compiled for.
+    pub fn native_label(&self) -> String {
+        self.label_for(Platform::current())
+    }
+}
+
+fn key_label(key: Key, platform: Platform) -> String {
+    let mac = platform == Platform::MacOs;
+    let windows = platform == Platform::Windows;
+    let text = match key {
+        Key::Char(' ') | Key::Space => "Space",
+        Key::Char(ch) => return ch.to_uppercase().collect(),
+        Key::Enter if mac => "↩",
+        Key::Enter => "Enter",
+        Key::Escape if mac => "⎋",
+        Key::Escape => "Esc",
+        Key::Tab if mac => "⇥",
+        Key::Tab => "Tab",
+        Key::Backspace if mac => "⌫",
+        Key::Backspace => "Backspace",
+        Key::Delete if mac => "⌦",
+        Key::Delete if windows => "Del",
+        Key::Delete => "Delete",
+        Key::Insert if windows => "Ins",
+        Key::Insert => "Insert",
+        Key::Home if mac => "↖",
+        Key::Home => "Home",
+        Key::End if mac => "↘",
+        Key::End => "End",
+        Key::PageUp if mac => "⇞",
+        Key::PageUp if windows => "PgUp",
+        Key::PageUp => "Page Up",
+        Key::PageDown if mac => "⇟",
+        Key::PageDown if windows => "PgDn",
+        Key::PageDown => "Page Down",
+        Key::ArrowUp if mac => "↑",
+        Key::ArrowUp => "Up",
+        Key::ArrowDown if mac => "↓",
+        Key::ArrowDown => "Down",
+        Key::ArrowLeft if mac => "←",
+        Key::ArrowLeft => "Left",
+        Key::ArrowRight if mac => "→",
+        Key::ArrowRight => "Right",
+        Key::PrintScreen if windows => "PrtScn",
+        Key::PrintScreen => "Print",
+        Key::ScrollLock if windows => "ScrLk",
+        Key::ScrollLock => "Scroll Lock",
+        Key::Pause => "Pause",
+        Key::ContextMenu => "Menu",
+        Key::Help => "Help",
+        Key::F(n) => return format!("F{n}"),
+        Key::Numpad(NumpadKey::Enter) if mac => "⌤",
+        Key::Numpad(NumpadKey::Enter) => "Num Enter",
+        Key::Numpad(key) => {
+            let ch = key.to_char().unwrap_or('?');
+            return if mac {
+                ch.to_string()
+            } else {
+                format!("Num {ch}")
+            };
+        }
+        Key::Media(MediaKey::PlayPause) => "Play/Pause",
+        Key::Media(MediaKey::Stop) => "Stop",
+        Key::Media(MediaKey::NextTrack) => "Next Track",
+        Key::Media(MediaKey::PreviousTrack) => "Previous Track",
+        Key::Media(MediaKey::VolumeUp) => "Volume Up",
+        Key::Media(MediaKey::VolumeDown) => "Volume Down",
+        Key::Media(MediaKey::VolumeMute) => "Mute",
+    };
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(s: &str, platform: Platform) -> String {
+        s.parse::<Shortcut>().unwrap().label_for(platform)
+    }
+
+    #[test]
+    fn macos_orders_modifier_glyphs() {
+        assert_eq!(label("Cmd+Shift+Alt+Ctrl+S", Platform::MacOs), "⌃⌥⇧⌘S");
+        assert_eq!(label("Cmd+,", Platform::MacOs), "⌘,");
+        assert_eq!(label("Shift+Enter", Platform::MacOs), "⇧↩");
+    }
+
+    #[test]
+    fn windows_joins_names_with_plus() {
+        assert_eq!(
+            label("Shift+Alt+Ctrl+S", Platform::Windows),
+            "Ctrl+Alt+Shift+S"
+        );
+        assert_eq!(label("Win+Delete", Platform::Windows), "Win+Del");
+        assert_eq!(label("Ctrl+PageDown", Platform::Windows), "Ctrl+PgDn");
+    }
+
+    #[test]
+    fn linux_spells_out_names() {
+        assert_eq!(
+            label("Meta+Shift+Alt+Ctrl+S", Platform::Linux),
+            "Ctrl+Alt+Shift+Super+S"
+        );
+        assert_eq!(label("Ctrl+PageDown", Platform::Linux), "Ctrl+Page Down");
+        assert_eq!(label("Ctrl+Space", Platform::Linux), "Ctrl+Space");
+    }
+
+    #[test]
+    fn arrow_keys() {
+        assert_eq!(label("Alt+Up", Platform::MacOs), "⌥↑");
+        assert_eq!(label("Alt+ArrowLeft", Platform::MacOs), "⌥←");
+        assert_eq!(label("Alt+Down", Platform::Windows), "Alt+Down");
+        assert_eq!(label("Alt+Right", Platform::Linux), "Alt+Right");
+    }
+
+    #[test]
+    fn numpad_keys() {
+        assert_eq!(label("Num5", Platform::MacOs), "5");
+        assert_eq!(label("Ctrl+NumAdd", Platform::MacOs), "⌃+");
+        assert_eq!(label("NumEnter", Platform::MacOs), "⌤");
+        assert_eq!(label("Ctrl+Numpad0", Platform::Windows), "Ctrl+Num 0");
+        assert_eq!(label("NumMultiply", Platform::Linux), "Num *");
+        assert_eq!(label("NumEnter", Platform::Linux), "Num Enter");
+    }
+
+    #[test]
+    fn media_keys() {
+        for platform in [Platform::MacOs, Platform::Windows, Platform::Linux] {
+            assert_eq!(label("MediaPlayPause", platform), "Play/Pause");
+            assert_eq!(label("MediaPrevTrack", platform), "Previous Track");
+            assert_eq!(label("VolumeMute", platform), "Mute");
+        }
+    }
+}
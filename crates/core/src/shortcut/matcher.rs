@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::command::CommandId;
+use crate::menu::{MenuBarModel, MenuItem, MenuModel};
+use crate::tray::TrayMenuModel;
+
+use super::{Key, Modifiers, SequenceMatch, Shortcut, ShortcutSequence};
+
+/// Maps key events to the commands whose `shortcut` they trigger.
+///
+/// Backends without native key equivalents (Linux, Windows) feed window key
+/// events through a matcher built from the same models passed to the menus.
+/// Only enabled commands are indexed; when several commands share a shortcut
+/// the first one in menu order wins, as it does in AppKit. Chords are
+/// resolved stroke by stroke through `lookup` or a `ShortcutResolver`.
+#[derive(Debug, Clone, Default)]
+pub struct ShortcutMatcher {
+    bindings: HashMap<ShortcutSequence, CommandId>,
+    prefixes: HashSet<ShortcutSequence>,
+}
+
+impl ShortcutMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_menu_bar(model: &MenuBarModel) -> Self {
+        let mut matcher = Self::new();
+        matcher.add_menu_bar(model);
+        matcher
+    }
+
+    pub fn from_menu(model: &MenuModel) -> Self {
+        let mut matcher = Self::new();
+        matcher.add_menu(model);
+        matcher
+    }
+
+    pub fn from_tray_menu(model: &TrayMenuModel) -> Self {
+        let mut matcher = Self::new();
+        matcher.add_tray_menu(model);
+        matcher
+    }
+
+    /// Drops all bindings but keeps the allocation, so rebuilding after a
+    /// model change is `clear` followed by the `add_*` calls.
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+        self.prefixes.clear();
+    }
+
+    pub fn add_menu_bar(&mut self, model: &MenuBarModel) {
+        for menu in &model.menus {
+            self.add_menu_items(&menu.items);
+        }
+    }
+
+    pub fn add_menu(&mut self, model: &MenuModel) {
+        self.add_menu_items(&model.items);
+    }
+
+    pub fn add_tray_menu(&mut self, model: &TrayMenuModel) {
+        self.add_menu_items(&model.items);
+    }
+
+    pub fn bind(&mut self, sequence: impl Into<ShortcutSequence>, command: CommandId) {
+        let sequence = sequence.into().normalized();
+        let strokes = sequence.strokes();
+        for len in 1..strokes.len() {
+            if let Some(prefix) = ShortcutSequence::new(&strokes[..len]) {
+                self.prefixes.insert(prefix);
+            }
+        }
+        self.bindings.entry(sequence).or_insert(command);
+    }
+
+    /// Single-stroke lookup; chord bindings never match here.
+    pub fn match_key(&self, key: Key, mods: Modifiers) -> Option<CommandId> {
+        self.match_shortcut(Shortcut::new(mods, key))
+    }
+
+    pub fn match_shortcut(&self, shortcut: Shortcut) -> Option<CommandId> {
+        self.bindings
+            .get(&ShortcutSequence::single(shortcut.normalized()))
+            .copied()
+    }
+
+    /// Looks up the strokes typed so far. A sequence that is both bound and a
+    /// prefix of a longer binding reports `Prefix`.
+    pub fn lookup(&self, strokes: &[Shortcut]) -> SequenceMatch {
+        let Some(sequence) = ShortcutSequence::new(strokes) else {
+            return SequenceMatch::None;
+        };
+        let sequence = sequence.normalized();
+        if self.prefixes.contains(&sequence) {
+            return SequenceMatch::Prefix;
+        }
+        match self.bindings.get(&sequence) {
+            Some(command) => SequenceMatch::Command(*command),
+            None => SequenceMatch::None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    fn add_menu_items(&mut self, items: &[MenuItem]) {
+        for item in items {
+            match item {
+                MenuItem::Command(cmd) => {
+                    if let (true, Some(shortcut)) = (cmd.enabled, cmd.shortcut) {
+                        self.bind(shortcut, cmd.id);
+                    }
+                }
+                MenuItem::Submenu(sub) => self.add_menu_items(&sub.items),
+                MenuItem::Separator(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::test_items::{bound, id, seq, stroke};
+    use crate::menu::{CommandItem, Submenu, TopMenu};
+
+    #[test]
+    fn only_enabled_commands_are_indexed() {
+        let mut disabled = CommandItem::new(id(2), "Print").with_shortcut(seq("Ctrl+P"));
+        disabled.enabled = false;
+        let matcher = ShortcutMatcher::from_menu(&MenuModel::new(vec![
+            bound(1, "Item", "Ctrl+S"),
+            MenuItem::Command(disabled),
+            MenuItem::Command(CommandItem::new(id(3), "Close")),
+        ]));
+        assert_eq!(matcher.len(), 1);
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+S")), Some(id(1)));
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+P")), None);
+    }
+
+    #[test]
+    fn first_binding_wins() {
+        let matcher = ShortcutMatcher::from_menu(&MenuModel::new(vec![
+            bound(1, "Item", "Ctrl+S"),
+            bound(2, "Item", "Ctrl+S"),
+        ]));
+        assert_eq!(matcher.len(), 1);
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+S")), Some(id(1)));
+
+        let mut matcher = matcher;
+        matcher.bind(seq("Ctrl+S"), id(3));
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+S")), Some(id(1)));
+    }
+
+    #[test]
+    fn finds_items_inside_submenus() {
+        let image = Submenu::new("Image", vec![bound(1, "Item", "Ctrl+Shift+E")]);
+        let export = Submenu::new("Export", vec![MenuItem::Submenu(image)]);
+        let matcher = ShortcutMatcher::from_menu(&MenuModel::new(vec![MenuItem::Submenu(export)]));
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+Shift+E")), Some(id(1)));
+    }
+
+    #[test]
+    fn builds_from_menu_bar_and_tray_menu() {
+        let bar = MenuBarModel::new(vec![
+            TopMenu::new("File", vec![bound(1, "Item", "Ctrl+O")]),
+            TopMenu::new("Edit", vec![bound(2, "Item", "Ctrl+Z")]),
+        ]);
+        let matcher = ShortcutMatcher::from_menu_bar(&bar);
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+O")), Some(id(1)));
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+Z")), Some(id(2)));
+
+        let tray = TrayMenuModel::new(vec![bound(3, "Item", "Ctrl+Q")]);
+        let matcher = ShortcutMatcher::from_tray_menu(&tray);
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::NONE
+        };
+        assert_eq!(matcher.match_key(Key::Char('Q'), ctrl), Some(id(3)));
+    }
+
+    #[test]
+    fn rebuilds_after_model_change() {
+        let mut model = MenuModel::new(vec![bound(1, "Item", "Ctrl+S")]);
+        let mut matcher = ShortcutMatcher::from_menu(&model);
+
+        model.items = vec![
+            bound(1, "Item", "Ctrl+Shift+S"),
+            bound(2, "Item", "Ctrl+K Ctrl+S"),
+        ];
+        matcher.clear();
+        matcher.add_menu(&model);
+
+        assert_eq!(matcher.len(), 2);
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+S")), None);
+        assert_eq!(matcher.match_shortcut(stroke("Ctrl+Shift+S")), Some(id(1)));
+        assert_eq!(matcher.lookup(&[stroke("Ctrl+K")]), SequenceMatch::Prefix);
+        assert_eq!(
+            matcher.lookup(&[stroke("Ctrl+K"), stroke("Ctrl+S")]),
+            SequenceMatch::Command(id(2))
+        );
+    }
+}
@@ -0,0 +1,159 @@
+pub mod conflict;
+pub mod keymap;
+pub mod label;
+pub mod matcher;
+pub mod parse;
+pub mod sequence;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+pub use conflict::*;
+pub use keymap::*;
+pub use matcher::*;
+pub use parse::*;
+pub use sequence::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub mods: Modifiers,
+    pub key: Key,
+}
+
+impl Shortcut {
+    pub const fn new(mods: Modifiers, key: Key) -> Self {
+        Self { mods, key }
+    }
+
+    /// Canonical form used for comparisons: letters lowercased, `' '` folded into `Space`.
+    pub fn normalized(self) -> Self {
+        Self {
+            mods: self.mods,
+            key: self.key.normalized(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        alt: false,
+        shift: false,
+        meta: false,
+    };
+
+    /// The platform's primary command modifier: Cmd on macOS, Ctrl elsewhere.
+    pub const fn cmd_or_ctrl() -> Self {
+        let mac = cfg!(target_os = "macos");
+        Self {
+            ctrl: !mac,
+            alt: false,
+            shift: false,
+            meta: mac,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !(self.ctrl || self.alt || self.shift || self.meta)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Escape,
+    Tab,
+    Space,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+    ContextMenu,
+    Help,
+    /// Function keys F1..F35. Backends ignore numbers they cannot represent.
+    F(u8),
+    Numpad(NumpadKey),
+    Media(MediaKey),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumpadKey {
+    Digit(NumpadDigit),
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Decimal,
+    Equal,
+    Enter,
+}
+
+/// A numpad digit, always 0..=9.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NumpadDigit(u8);
+
+impl NumpadDigit {
+    pub const fn new(digit: u8) -> Option<Self> {
+        if digit <= 9 { Some(Self(digit)) } else { None }
+    }
+
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl Key {
+    pub fn normalized(self) -> Self {
+        match self {
+            Key::Char(' ') => Key::Space,
+            Key::Char(ch) => Key::Char(ch.to_lowercase().next().unwrap_or(ch)),
+            key => key,
+        }
+    }
+}
+
+impl NumpadKey {
+    /// Character produced by the key, if any.
+    pub fn to_char(self) -> Option<char> {
+        match self {
+            NumpadKey::Digit(n) => char::from_digit(n.get() as u32, 10),
+            NumpadKey::Add => Some('+'),
+            NumpadKey::Subtract => Some('-'),
+            NumpadKey::Multiply => Some('*'),
+            NumpadKey::Divide => Some('/'),
+            NumpadKey::Decimal => Some('.'),
+            NumpadKey::Equal => Some('='),
+            NumpadKey::Enter => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaKey {
+    PlayPause,
+    Stop,
+    NextTrack,
+    PreviousTrack,
+    VolumeUp,
+    VolumeDown,
+    VolumeMute,
+}
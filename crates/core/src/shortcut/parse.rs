@@ -0,0 +1,586 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Key, MediaKey, Modifiers, NumpadDigit, NumpadKey, Shortcut};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseShortcutError {
+    kind: ParseShortcutErrorKind,
+    token: String,
+    position: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseShortcutErrorKind {
+    Empty,
+    UnknownModifier,
+    UnknownKey,
+    MissingKey,
+    TooManyStrokes,
+}
+
+impl ParseShortcutError {
+    pub(crate) fn new(kind: ParseShortcutErrorKind, token: &str, position: usize) -> Self {
+        Self {
+            kind,
+            token: token.to_string(),
+            position,
+        }
+    }
+
+    pub fn kind(&self) -> ParseShortcutErrorKind {
+        self.kind
+    }
+
+    /// The offending token, as written in the input.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Byte offset of the offending token in the input.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseShortcutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseShortcutErrorKind::Empty => write!(f, "empty shortcut"),
+            ParseShortcutErrorKind::UnknownModifier if self.token.is_empty() => {
+                write!(f, "empty modifier at position {}", self.position)
+            }
+            ParseShortcutErrorKind::UnknownModifier => {
+                write!(
+                    f,
+                    "unknown modifier `{}` at position {}",
+                    self.token, self.position
+                )
+            }
+            ParseShortcutErrorKind::UnknownKey => {
+                write!(
+                    f,
+                    "unknown key `{}` at position {}",
+                    self.token, self.position
+                )
+            }
+            ParseShortcutErrorKind::MissingKey => {
+                write!(f, "missing key at position {}", self.position)
+            }
+            ParseShortcutErrorKind::TooManyStrokes => {
+                write!(
+                    f,
+                    "too many strokes at position {} (at most {})",
+                    self.position,
+                    super::ShortcutSequence::MAX_LEN
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseShortcutError {}
+
+/// Parses shortcuts such as `"CmdOrCtrl+Shift+S"`, `"Alt+F4"` or `"Ctrl++"`.
+///
+/// Grammar (tokens are case-insensitive, no whitespace):
+///
+/// ```text
+/// shortcut = { modifier "+" } key
+/// modifier = "Ctrl" | "Control"
+///          | "Alt" | "Option" | "Opt"
+///          | "Shift"
+///          | "Meta" | "Cmd" | "Command" | "Super" | "Win"
+///          | "CmdOrCtrl" | "CommandOrControl"
+/// key      = any single character | key name (see `Key::from_str`)
+/// ```
+///
+/// `CmdOrCtrl` resolves to Cmd on macOS and Ctrl elsewhere at parse time.
+/// A literal `+` key is written as `Plus` or as a trailing `++`.
+impl FromStr for Shortcut {
+    type Err = ParseShortcutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_shortcut_in(s.as_bytes(), 0, s.len()).map_err(|err| err.into_error(s))
+    }
+}
+
+impl Shortcut {
+    /// `FromStr` for const contexts; only the error kind is reported.
+    pub const fn parse_const(s: &str) -> Result<Self, ParseShortcutErrorKind> {
+        match parse_shortcut_in(s.as_bytes(), 0, s.len()) {
+            Ok(shortcut) => Ok(shortcut),
+            Err(err) => Err(err.kind),
+        }
+    }
+}
+
+/// A parse error as a byte range, so the parser can run in const contexts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RawParseError {
+    pub(crate) kind: ParseShortcutErrorKind,
+    start: usize,
+    end: usize,
+}
+
+impl RawParseError {
+    pub(crate) const fn new(kind: ParseShortcutErrorKind, start: usize, end: usize) -> Self {
+        Self { kind, start, end }
+    }
+
+    pub(crate) fn into_error(self, s: &str) -> ParseShortcutError {
+        ParseShortcutError::new(self.kind, &s[self.start..self.end], self.start)
+    }
+}
+
+/// Parses `s[start..end]` as one shortcut; error positions are offsets into `s`.
+pub(crate) const fn parse_shortcut_in(
+    s: &[u8],
+    start: usize,
+    end: usize,
+) -> Result<Shortcut, RawParseError> {
+    if start == end {
+        return Err(RawParseError::new(
+            ParseShortcutErrorKind::Empty,
+            start,
+            end,
+        ));
+    }
+
+    let (mods_end, key_start) = if end - start == 1 && s[start] == b'+' {
+        (None, start)
+    } else if end - start >= 2 && s[end - 1] == b'+' && s[end - 2] == b'+' {
+        (Some(end - 2), end - 1)
+    } else {
+        match rfind_byte(s, b'+', start, end) {
+            Some(index) => (Some(index), index + 1),
+            None => (None, start),
+        }
+    };
+
+    let mut mods = Modifiers::NONE;
+    if let Some(mods_end) = mods_end {
+        let mut token_start = start;
+        loop {
+            let token_end = find_byte(s, b'+', token_start, mods_end);
+            mods = match apply_modifier(mods, s, token_start, token_end) {
+                Some(mods) => mods,
+                None => {
+                    return Err(RawParseError::new(
+                        ParseShortcutErrorKind::UnknownModifier,
+                        token_start,
+                        token_end,
+                    ));
+                }
+            };
+            if token_end == mods_end {
+                break;
+            }
+            token_start = token_end + 1;
+        }
+    }
+
+    if key_start == end {
+        return Err(RawParseError::new(
+            ParseShortcutErrorKind::MissingKey,
+            key_start,
+            end,
+        ));
+    }
+    match parse_key_in(s, key_start, end) {
+        Some(key) => Ok(Shortcut { mods, key }),
+        None => Err(RawParseError::new(
+            ParseShortcutErrorKind::UnknownKey,
+            key_start,
+            end,
+        )),
+    }
+}
+
+/// Index of the first `byte` in `s[start..end]`, or `end`.
+pub(crate) const fn find_byte(s: &[u8], byte: u8, start: usize, end: usize) -> usize {
+    let mut index = start;
+    while index < end && s[index] != byte {
+        index += 1;
+    }
+    index
+}
+
+const fn rfind_byte(s: &[u8], byte: u8, start: usize, end: usize) -> Option<usize> {
+    let mut index = end;
+    while index > start {
+        index -= 1;
+        if s[index] == byte {
+            return Some(index);
+        }
+    }
+    None
+}
+
+const fn token_eq(s: &[u8], start: usize, end: usize, name: &[u8]) -> bool {
+    if end - start != name.len() {
+        return false;
+    }
+    let mut index = 0;
+    while index < name.len() {
+        if !s[start + index].eq_ignore_ascii_case(&name[index]) {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+const fn apply_modifier(
+    mut mods: Modifiers,
+    s: &[u8],
+    start: usize,
+    end: usize,
+) -> Option<Modifiers> {
+    if token_eq(s, start, end, b"ctrl") || token_eq(s, start, end, b"control") {
+        mods.ctrl = true;
+    } else if token_eq(s, start, end, b"alt")
+        || token_eq(s, start, end, b"option")
+        || token_eq(s, start, end, b"opt")
+    {
+        mods.alt = true;
+    } else if token_eq(s, start, end, b"shift") {
+        mods.shift = true;
+    } else if token_eq(s, start, end, b"meta")
+        || token_eq(s, start, end, b"cmd")
+        || token_eq(s, start, end, b"command")
+        || token_eq(s, start, end, b"super")
+        || token_eq(s, start, end, b"win")
+    {
+        mods.meta = true;
+    } else if token_eq(s, start, end, b"cmdorctrl") || token_eq(s, start, end, b"commandorcontrol")
+    {
+        let primary = Modifiers::cmd_or_ctrl();
+        mods.ctrl |= primary.ctrl;
+        mods.meta |= primary.meta;
+    } else {
+        return None;
+    }
+    Some(mods)
+}
+
+/// Parses a single key name such as `"S"`, `"F5"`, `"PageUp"` or `"Num0"`.
+impl FromStr for Key {
+    type Err = ParseShortcutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseShortcutError::new(
+                ParseShortcutErrorKind::MissingKey,
+                s,
+                0,
+            ));
+        }
+        parse_key_in(s.as_bytes(), 0, s.len())
+            .ok_or_else(|| ParseShortcutError::new(ParseShortcutErrorKind::UnknownKey, s, 0))
+    }
+}
+
+const KEY_NAMES: &[(&str, Key)] = &[
+    ("plus", Key::Char('+')),
+    ("enter", Key::Enter),
+    ("return", Key::Enter),
+    ("escape", Key::Escape),
+    ("esc", Key::Escape),
+    ("tab", Key::Tab),
+    ("space", Key::Space),
+    ("backspace", Key::Backspace),
+    ("delete", Key::Delete),
+    ("del", Key::Delete),
+    ("insert", Key::Insert),
+    ("ins", Key::Insert),
+    ("home", Key::Home),
+    ("end", Key::End),
+    ("pageup", Key::PageUp),
+    ("pgup", Key::PageUp),
+    ("pagedown", Key::PageDown),
+    ("pgdn", Key::PageDown),
+    ("pgdown", Key::PageDown),
+    ("up", Key::ArrowUp),
+    ("arrowup", Key::ArrowUp),
+    ("down", Key::ArrowDown),
+    ("arrowdown", Key::ArrowDown),
+    ("left", Key::ArrowLeft),
+    ("arrowleft", Key::ArrowLeft),
+    ("right", Key::ArrowRight),
+    ("arrowright", Key::ArrowRight),
+    ("printscreen", Key::PrintScreen),
+    ("prtsc", Key::PrintScreen),
+    ("prtscn", Key::PrintScreen),
+    ("scrolllock", Key::ScrollLock),
+    ("pause", Key::Pause),
+    ("contextmenu", Key::ContextMenu),
+    ("menu", Key::ContextMenu),
+    ("help", Key::Help),
+    ("numadd", Key::Numpad(NumpadKey::Add)),
+    ("numplus", Key::Numpad(NumpadKey::Add)),
+    ("numsubtract", Key::Numpad(NumpadKey::Subtract)),
+    ("numminus", Key::Numpad(NumpadKey::Subtract)),
+    ("nummultiply", Key::Numpad(NumpadKey::Multiply)),
+    ("numdivide", Key::Numpad(NumpadKey::Divide)),
+    ("numdecimal", Key::Numpad(NumpadKey::Decimal)),
+    ("numequal", Key::Numpad(NumpadKey::Equal)),
+    ("numenter", Key::Numpad(NumpadKey::Enter)),
+    ("mediaplaypause", Key::Media(MediaKey::PlayPause)),
+    ("mediastop", Key::Media(MediaKey::Stop)),
+    ("medianexttrack", Key::Media(MediaKey::NextTrack)),
+    ("mediaprevioustrack", Key::Media(MediaKey::PreviousTrack)),
+    ("mediaprevtrack", Key::Media(MediaKey::PreviousTrack)),
+    ("volumeup", Key::Media(MediaKey::VolumeUp)),
+    ("volumedown", Key::Media(MediaKey::VolumeDown)),
+    ("volumemute", Key::Media(MediaKey::VolumeMute)),
+];
+
+const fn parse_key_in(s: &[u8], start: usize, end: usize) -> Option<Key> {
+    if let Some(ch) = single_char(s, start, end) {
+        return Some(match ch {
+            ' ' => Key::Space,
+            ch => Key::Char(ch.to_ascii_lowercase()),
+        });
+    }
+
+    let mut index = 0;
+    while index < KEY_NAMES.len() {
+        let (name, key) = KEY_NAMES[index];
+        if token_eq(s, start, end, name.as_bytes()) {
+            return Some(key);
+        }
+        index += 1;
+    }
+    parse_numbered_key(s, start, end)
+}
+
+/// The token's only character, decoding UTF-8 by hand since `str` slicing isn't const.
+const fn single_char(s: &[u8], start: usize, end: usize) -> Option<char> {
+    let first = s[start];
+    let (width, init) = match first {
+        0x00..=0x7F => (1, first as u32),
+        0xC0..=0xDF => (2, (first & 0x1F) as u32),
+        0xE0..=0xEF => (3, (first & 0x0F) as u32),
+        0xF0..=0xF7 => (4, (first & 0x07) as u32),
+        _ => return None,
+    };
+    if end - start != width {
+        return None;
+    }
+    let mut code = init;
+    let mut index = start + 1;
+    while index < end {
+        code = (code << 6) | (s[index] & 0x3F) as u32;
+        index += 1;
+    }
+    char::from_u32(code)
+}
+
+const fn parse_numbered_key(s: &[u8], start: usize, end: usize) -> Option<Key> {
+    if s[start].eq_ignore_ascii_case(&b'f') {
+        return match parse_u8(s, start + 1, end) {
+            Some(n) if n >= 1 && n <= 35 => Some(Key::F(n)),
+            _ => None,
+        };
+    }
+    let digit = end - 1;
+    if !token_eq(s, start, digit, b"numpad") && !token_eq(s, start, digit, b"num") {
+        return None;
+    }
+    match parse_u8(s, digit, end) {
+        Some(n) => match NumpadDigit::new(n) {
+            Some(n) => Some(Key::Numpad(NumpadKey::Digit(n))),
+            None => None,
+        },
+        None => None,
+    }
+}
+
+const fn parse_u8(s: &[u8], start: usize, end: usize) -> Option<u8> {
+    if start == end {
+        return None;
+    }
+    let mut value: u32 = 0;
+    let mut index = start;
+    while index < end {
+        let byte = s[index];
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        value = value * 10 + (byte - b'0') as u32;
+        if value > u8::MAX as u32 {
+            return None;
+        }
+        index += 1;
+    }
+    Some(value as u8)
+}
+
+/// Formats the canonical, portable form accepted by `FromStr`, e.g. `"Ctrl+Shift+S"`.
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mods.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.mods.alt {
+            f.write_str("Alt+")?;
+        }
+        if self.mods.shift {
+            f.write_str("Shift+")?;
+        }
+        if self.mods.meta {
+            f.write_str("Meta+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Key::Char('+') => "Plus",
+            Key::Char(' ') => "Space",
+            Key::Char(ch) => return write!(f, "{}", ch.to_ascii_uppercase()),
+            Key::Enter => "Enter",
+            Key::Escape => "Escape",
+            Key::Tab => "Tab",
+            Key::Space => "Space",
+            Key::Backspace => "Backspace",
+            Key::Delete => "Delete",
+            Key::Insert => "Insert",
+            Key::Home => "Home",
+            Key::End => "End",
+            Key::PageUp => "PageUp",
+            Key::PageDown => "PageDown",
+            Key::ArrowUp => "Up",
+            Key::ArrowDown => "Down",
+            Key::ArrowLeft => "Left",
+            Key::ArrowRight => "Right",
+            Key::PrintScreen => "PrintScreen",
+            Key::ScrollLock => "ScrollLock",
+            Key::Pause => "Pause",
+            Key::ContextMenu => "ContextMenu",
+            Key::Help => "Help",
+            Key::F(n) => return write!(f, "F{n}"),
+            Key::Numpad(NumpadKey::Digit(n)) => return write!(f, "Num{}", n.get()),
+            Key::Numpad(NumpadKey::Add) => "NumAdd",
+            Key::Numpad(NumpadKey::Subtract) => "NumSubtract",
+            Key::Numpad(NumpadKey::Multiply) => "NumMultiply",
+            Key::Numpad(NumpadKey::Divide) => "NumDivide",
+            Key::Numpad(NumpadKey::Decimal) => "NumDecimal",
+            Key::Numpad(NumpadKey::Equal) => "NumEqual",
+            Key::Numpad(NumpadKey::Enter) => "NumEnter",
+            Key::Media(MediaKey::PlayPause) => "MediaPlayPause",
+            Key::Media(MediaKey::Stop) => "MediaStop",
+            Key::Media(MediaKey::NextTrack) => "MediaNextTrack",
+            Key::Media(MediaKey::PreviousTrack) => "MediaPreviousTrack",
+            Key::Media(MediaKey::VolumeUp) => "VolumeUp",
+            Key::Media(MediaKey::VolumeDown) => "VolumeDown",
+            Key::Media(MediaKey::VolumeMute) => "VolumeMute",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Shortcut {
+        s.parse().unwrap_or_else(|err| panic!("{s}: {err}"))
+    }
+
+    fn error(s: &str) -> (ParseShortcutErrorKind, String, usize) {
+        let err = s.parse::<Shortcut>().unwrap_err();
+        (err.kind(), err.token().to_string(), err.position())
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let shortcut = parse("ctrl+SHIFT+s");
+        assert_eq!(
+            shortcut,
+            Shortcut::new(
+                Modifiers {
+                    ctrl: true,
+                    shift: true,
+                    ..Modifiers::NONE
+                },
+                Key::Char('s'),
+            )
+        );
+        assert!(parse("Option+Cmd+Left").mods.alt);
+        assert!(parse("Win+E").mods.meta);
+        assert_eq!(parse("CmdOrCtrl+O").mods, Modifiers::cmd_or_ctrl());
+        assert_eq!(parse("Alt+F4").key, Key::F(4));
+        assert_eq!(parse("Num7").key.to_string(), "Num7");
+        assert_eq!(parse("Esc").key, Key::Escape);
+    }
+
+    #[test]
+    fn plus_key_forms() {
+        let plus = Key::Char('+');
+        assert_eq!(parse("+").key, plus);
+        assert_eq!(parse("Ctrl++").key, plus);
+        assert_eq!(parse("Ctrl+Plus"), parse("Ctrl++"));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in [
+            "Ctrl+Shift+S",
+            "Alt+F4",
+            "Meta+Plus",
+            "Ctrl+Alt+Shift+Meta+Delete",
+            "Shift+Space",
+            "NumEnter",
+            "Num0",
+            "Ctrl+NumAdd",
+            "F35",
+            "Up",
+            "PageDown",
+            "Ctrl+VolumeUp",
+        ] {
+            let shortcut = parse(text);
+            assert_eq!(shortcut.to_string(), text);
+            assert_eq!(parse(&shortcut.to_string()), shortcut);
+        }
+        // Aliases format in their canonical spelling.
+        assert_eq!(parse("control+option+return").to_string(), "Ctrl+Alt+Enter");
+    }
+
+    #[test]
+    fn rejects_malformed_shortcuts() {
+        use ParseShortcutErrorKind::*;
+        assert_eq!(error(""), (Empty, String::new(), 0));
+        assert_eq!(error("Hyper+S"), (UnknownModifier, "Hyper".to_string(), 0));
+        assert_eq!(error("Ctrl++S"), (UnknownModifier, String::new(), 5));
+        assert_eq!(error("Ctrl+"), (MissingKey, String::new(), 5));
+        assert_eq!(error("Ctrl+Shift+Foo"), (UnknownKey, "Foo".to_string(), 11));
+        assert_eq!(error("F0").0, UnknownKey);
+        assert_eq!(error("F36").0, UnknownKey);
+        assert_eq!(error("Num10").0, UnknownKey);
+        assert_eq!(error("Num256").0, UnknownKey);
+    }
+
+    #[test]
+    fn const_parse_matches_from_str() {
+        const SAVE: Result<Shortcut, ParseShortcutErrorKind> = Shortcut::parse_const("Ctrl+S");
+        assert_eq!(SAVE, Ok(parse("Ctrl+S")));
+        assert_eq!(
+            Shortcut::parse_const("Ctrl+Nope"),
+            Err(ParseShortcutErrorKind::UnknownKey)
+        );
+    }
+
+    #[test]
+    fn numpad_digits_stay_in_range() {
+        assert_eq!(NumpadDigit::new(9).map(NumpadDigit::get), Some(9));
+        assert_eq!(NumpadDigit::new(10), None);
+        assert_eq!(
+            NumpadKey::Digit(NumpadDigit::new(3).unwrap()).to_char(),
+            Some('3')
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::command::CommandId;
+use crate::platform::Platform;
+
+use super::parse::{
+    ParseShortcutError, ParseShortcutErrorKind, RawParseError, find_byte, parse_shortcut_in,
+};
+use super::{Key, Modifiers, Shortcut, ShortcutMatcher};
+
+/// One or more shortcuts pressed in order, e.g. `Ctrl+K Ctrl+S`.
+///
+/// A single-stroke sequence behaves exactly like the `Shortcut` it wraps.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShortcutSequence {
+    strokes: [Shortcut; ShortcutSequence::MAX_LEN],
+    len: u8,
+}
+
+const UNUSED_STROKE: Shortcut = Shortcut::new(Modifiers::NONE, Key::Char('\0'));
+
+impl ShortcutSequence {
+    pub const MAX_LEN: usize = 4;
+
+    pub const fn single(shortcut: Shortcut) -> Self {
+        let mut strokes = [UNUSED_STROKE; Self::MAX_LEN];
+        strokes[0] = shortcut;
+        Self { strokes, len: 1 }
+    }
+
+    /// Returns `None` for an empty slice or more than `MAX_LEN` strokes.
+    pub fn new(strokes: &[Shortcut]) -> Option<Self> {
+        if strokes.is_empty() || strokes.len() > Self::MAX_LEN {
+            return None;
+        }
+        let mut out = [UNUSED_STROKE; Self::MAX_LEN];
+        out[..strokes.len()].copy_from_slice(strokes);
+        Some(Self {
+            strokes: out,
+            len: strokes.len() as u8,
+        })
+    }
+
+    pub fn strokes(&self) -> &[Shortcut] {
+        &self.strokes[..self.len as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_chord(&self) -> bool {
+        self.len > 1
+    }
+
+    pub fn first(&self) -> Shortcut {
+        self.strokes[0]
+    }
+
+    /// The shortcut, if this is not a chord.
+    pub fn as_single(&self) -> Option<Shortcut> {
+        (self.len == 1).then_some(self.strokes[0])
+    }
+
+    pub fn starts_with(&self, prefix: &[Shortcut]) -> bool {
+        self.strokes().starts_with(prefix)
+    }
+
+    pub fn normalized(self) -> Self {
+        let mut out = self;
+        for stroke in &mut out.strokes[..self.len as usize] {
+            *stroke = stroke.normalized();
+        }
+        out
+    }
+
+    /// Strokes rendered with `Shortcut::label_for`, joined the way the platform writes chords.
+    pub fn label_for(&self, platform: Platform) -> String {
+        let separator = if platform == Platform::Windows {
+            ", "
+        } else {
+            " "
+        };
+        self.strokes()
+            .iter()
+            .map(|stroke| stroke.label_for(platform))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    pub fn native_label(&self) -> String {
+        self.label_for(Platform::current())
+    }
+}
+
+impl From<Shortcut> for ShortcutSequence {
+    fn from(shortcut: Shortcut) -> Self {
+        Self::single(shortcut)
+    }
+}
+
+/// Parses space-separated shortcuts, each following the `Shortcut` grammar:
+/// `"Ctrl+K Ctrl+S"`. Error positions are byte offsets into the whole input.
+impl FromStr for ShortcutSequence {
+    type Err = ParseShortcutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_sequence_in(s.as_bytes()).map_err(|err| err.into_error(s))
+    }
+}
+
+impl ShortcutSequence {
+    /// `FromStr` for const contexts; only the error kind is reported.
+    pub const fn parse_const(s: &str) -> Result<Self, ParseShortcutErrorKind> {
+        match parse_sequence_in(s.as_bytes()) {
+            Ok(sequence) => Ok(sequence),
+            Err(err) => Err(err.kind),
+        }
+    }
+}
+
+const fn parse_sequence_in(s: &[u8]) -> Result<ShortcutSequence, RawParseError> {
+    let mut strokes = [UNUSED_STROKE; ShortcutSequence::MAX_LEN];
+    let mut len = 0;
+    let mut start = 0;
+    while start <= s.len() {
+        let end = find_byte(s, b' ', start, s.len());
+        if end > start {
+            if len == ShortcutSequence::MAX_LEN {
+                return Err(RawParseError::new(
+                    ParseShortcutErrorKind::TooManyStrokes,
+                    start,
+                    end,
+                ));
+            }
+            strokes[len] = match parse_shortcut_in(s, start, end) {
+                Ok(stroke) => stroke,
+                Err(err) => return Err(err),
+            };
+            len += 1;
+        }
+        start = end + 1;
+    }
+    if len == 0 {
+        return Err(RawParseError::new(
+            ParseShortcutErrorKind::Empty,
+            0,
+            s.len(),
+        ));
+    }
+    Ok(ShortcutSequence {
+        strokes,
+        len: len as u8,
+    })
+}
+
+impl fmt::Debug for ShortcutSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ShortcutSequence")
+            .field(&self.strokes())
+            .finish()
+    }
+}
+
+impl fmt::Display for ShortcutSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, stroke) in self.strokes().iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{stroke}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceMatch {
+    Command(CommandId),
+    /// The strokes so far begin at least one longer binding.
+    Prefix,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutResolution {
+    Command(CommandId),
+    /// Waiting for the next stroke of the contained partial sequence.
+    Pending(ShortcutSequence),
+    /// A pending chord was abandoned by a stroke that continues no binding.
+    /// The stroke is consumed.
+    Cancelled,
+    /// Not a shortcut; the key should be handled normally.
+    Unmatched,
+}
+
+/// Feeds key strokes through a `ShortcutMatcher`, tracking chord prefixes.
+///
+/// A stroke that both completes a single-stroke binding and starts a chord is
+/// treated as a chord prefix.
+#[derive(Debug, Clone)]
+pub struct ShortcutResolver {
+    pending: Vec<Shortcut>,
+    last_stroke: Option<Instant>,
+    timeout: Duration,
+}
+
+impl Default for ShortcutResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShortcutResolver {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1500);
+
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            last_stroke: None,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn resolve(
+        &mut self,
+        matcher: &ShortcutMatcher,
+        key: Key,
+        mods: Modifiers,
+    ) -> ShortcutResolution {
+        self.resolve_at(matcher, Shortcut::new(mods, key), Instant::now())
+    }
+
+    pub fn resolve_at(
+        &mut self,
+        matcher: &ShortcutMatcher,
+        stroke: Shortcut,
+        now: Instant,
+    ) -> ShortcutResolution {
+        self.expire(now);
+        let was_pending = !self.pending.is_empty();
+        self.pending.push(stroke);
+
+        match matcher.lookup(&self.pending) {
+            SequenceMatch::Command(command) => {
+                self.cancel();
+                ShortcutResolution::Command(command)
+            }
+            SequenceMatch::Prefix => {
+                self.last_stroke = Some(now);
+                match ShortcutSequence::new(&self.pending) {
+                    Some(sequence) => ShortcutResolution::Pending(sequence),
+                    None => {
+                        self.cancel();
+                        ShortcutResolution::Cancelled
+                    }
+                }
+            }
+            SequenceMatch::None => {
+                self.cancel();
+                if was_pending {
+                    ShortcutResolution::Cancelled
+                } else {
+                    ShortcutResolution::Unmatched
+                }
+            }
+        }
+    }
+
+    /// Drops a pending chord whose timeout has elapsed. Returns `true` if one was dropped.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        match self.last_stroke {
+            Some(last) if now.saturating_duration_since(last) > self.timeout => {
+                self.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+        self.last_stroke = None;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn pending(&self) -> Option<ShortcutSequence> {
+        ShortcutSequence::new(&self.pending)
+    }
+
+    /// When the pending chord times out, for scheduling a wake-up.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.last_stroke.map(|last| last + self.timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::test_items::{id, seq, stroke};
+
+    fn matcher() -> ShortcutMatcher {
+        let mut matcher = ShortcutMatcher::new();
+        matcher.bind(seq("Ctrl+K Ctrl+S"), id(1));
+        matcher.bind(seq("Ctrl+K Ctrl+C"), id(2));
+        matcher.bind(stroke("Ctrl+O"), id(3));
+        matcher
+    }
+
+    #[test]
+    fn parses_and_formats_chords() {
+        let chord = seq("  Ctrl+K   Ctrl+S ");
+        assert_eq!(chord.strokes(), &[stroke("Ctrl+K"), stroke("Ctrl+S")]);
+        assert_eq!(chord.to_string(), "Ctrl+K Ctrl+S");
+        assert_eq!(seq("Alt+F4").as_single(), Some(stroke("Alt+F4")));
+
+        let err = "A B C D E".parse::<ShortcutSequence>().unwrap_err();
+        assert_eq!(err.kind(), ParseShortcutErrorKind::TooManyStrokes);
+        assert_eq!((err.token(), err.position()), ("E", 8));
+        let err = "Ctrl+K Ctrl+Bogus".parse::<ShortcutSequence>().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.position()),
+            (ParseShortcutErrorKind::UnknownKey, 12)
+        );
+        assert_eq!(
+            ShortcutSequence::parse_const("   "),
+            Err(ParseShortcutErrorKind::Empty)
+        );
+    }
+
+    #[test]
+    fn resolves_single_strokes_and_chords() {
+        let matcher = matcher();
+        let mut resolver = ShortcutResolver::new();
+        let now = Instant::now();
+
+        assert_eq!(
+            resolver.resolve_at(&matcher, stroke("Ctrl+O"), now),
+            ShortcutResolution::Command(id(3))
+        );
+        assert_eq!(
+            resolver.resolve_at(&matcher, stroke("Ctrl+K"), now),
+            ShortcutResolution::Pending(seq("Ctrl+K"))
+        );
+        assert!(resolver.is_pending());
+        assert_eq!(
+            resolver.deadline(),
+            Some(now + ShortcutResolver::DEFAULT_TIMEOUT)
+        );
+        assert_eq!(
+            resolver.resolve_at(&matcher, stroke("Ctrl+C"), now),
+            ShortcutResolution::Command(id(2))
+        );
+        assert!(!resolver.is_pending());
+        assert_eq!(resolver.deadline(), None);
+    }
+
+    #[test]
+    fn mismatch_cancels_pending_chord() {
+        let matcher = matcher();
+        let mut resolver = ShortcutResolver::new();
+        let now = Instant::now();
+
+        assert_eq!(
+            resolver.resolve_at(&matcher, stroke("Ctrl+X"), now),
+            ShortcutResolution::Unmatched
+        );
+        resolver.resolve_at(&matcher, stroke("Ctrl+K"), now);
+        // Ctrl+O would match on its own, but it is consumed by the cancelled chord.
+        assert_eq!(
+            resolver.resolve_at(&matcher, stroke("Ctrl+O"), now),
+            ShortcutResolution::Cancelled
+        );
+        assert!(!resolver.is_pending());
+        assert_eq!(
+            resolver.resolve_at(&matcher, stroke("Ctrl+O"), now),
+            ShortcutResolution::Command(id(3))
+        );
+    }
+
+    #[test]
+    fn pending_chord_times_out() {
+        let matcher = matcher();
+        let timeout = Duration::from_millis(100);
+        let mut resolver = ShortcutResolver::new().with_timeout(timeout);
+        let start = Instant::now();
+
+        resolver.resolve_at(&matcher, stroke("Ctrl+K"), start);
+        assert!(!resolver.expire(start + timeout));
+        assert_eq!(resolver.pending(), Some(seq("Ctrl+K")));
+
+        // The second stroke arrives too late and starts over on its own.
+        let late = start + timeout + Duration::from_millis(1);
+        assert_eq!(
+            resolver.resolve_at(&matcher, stroke("Ctrl+S"), late),
+            ShortcutResolution::Unmatched
+        );
+        assert!(!resolver.is_pending());
+
+        resolver.resolve_at(&matcher, stroke("Ctrl+K"), start);
+        assert!(resolver.expire(late));
+        assert_eq!(resolver.pending(), None);
+    }
+}
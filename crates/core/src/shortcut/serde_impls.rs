@@ -0,0 +1,101 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use super::{Key, Shortcut, ShortcutSequence};
+
+// Shortcuts use their `Display`/`FromStr` form ("Ctrl+Shift+S", "Ctrl+K Ctrl+S").
+// `CmdOrCtrl` is resolved when parsing, so it is written back as `Meta` or `Ctrl`.
+macro_rules! serde_via_str {
+    ($($ty:ty => $expecting:literal),* $(,)?) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_str(FromStrVisitor::<$ty>($expecting, PhantomData))
+            }
+        }
+    )*};
+}
+
+serde_via_str! {
+    Shortcut => "a shortcut such as \"Ctrl+Shift+S\"",
+    ShortcutSequence => "a shortcut sequence such as \"Ctrl+K Ctrl+S\"",
+    Key => "a key name such as \"S\" or \"PageUp\"",
+}
+
+struct FromStrVisitor<T>(&'static str, PhantomData<T>);
+
+impl<T> Visitor<'_> for FromStrVisitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_json<T: Serialize>(value: &T) -> String {
+        serde_json::to_string(value).unwrap()
+    }
+
+    #[test]
+    fn shortcuts_are_strings() {
+        let shortcut: Shortcut = "ctrl+shift+s".parse().unwrap();
+        assert_eq!(to_json(&shortcut), r#""Ctrl+Shift+S""#);
+        assert_eq!(
+            serde_json::from_str::<Shortcut>(r#""Ctrl+Shift+S""#).unwrap(),
+            shortcut
+        );
+
+        let sequence: ShortcutSequence = "Ctrl+K Ctrl+S".parse().unwrap();
+        assert_eq!(to_json(&sequence), r#""Ctrl+K Ctrl+S""#);
+        assert_eq!(
+            serde_json::from_str::<ShortcutSequence>(&to_json(&sequence)).unwrap(),
+            sequence
+        );
+
+        assert_eq!(to_json(&Key::PageUp), r#""PageUp""#);
+        assert_eq!(
+            serde_json::from_str::<Key>(r#""pgup""#).unwrap(),
+            Key::PageUp
+        );
+    }
+
+    #[test]
+    fn cmd_or_ctrl_is_written_resolved() {
+        let shortcut: Shortcut = serde_json::from_str(r#""CmdOrCtrl+S""#).unwrap();
+        let expected = if cfg!(target_os = "macos") {
+            r#""Meta+S""#
+        } else {
+            r#""Ctrl+S""#
+        };
+        assert_eq!(to_json(&shortcut), expected);
+    }
+
+    #[test]
+    fn bad_strings_report_the_parse_error() {
+        let err = serde_json::from_str::<Shortcut>(r#""Ctrl+Nope""#).unwrap_err();
+        assert!(err.to_string().contains("unknown key `Nope`"), "{err}");
+        let err = serde_json::from_str::<Shortcut>("1").unwrap_err();
+        assert!(err.to_string().contains("a shortcut such as"), "{err}");
+    }
+}
@@ -1,102 +1,42 @@
-use crate::command::CommandId;
-use crate::shortcut::Shortcut;
+use crate::icon::Icon;
+use crate::menu::{CommandItem, MenuItem, MenuItemRole, MenuModel, Submenu};
 
-#[derive(Clone, Debug)]
-pub enum TrayIcon {
-    Png { bytes: Vec<u8>, is_template: bool },
-}
-
-impl TrayIcon {
-    pub fn from_png_bytes(bytes: impl Into<Vec<u8>>) -> Self {
-        Self::Png {
-            bytes: bytes.into(),
-            is_template: false,
-        }
-    }
-
-    pub fn with_template(mut self, is_template: bool) -> Self {
-        let TrayIcon::Png { is_template: flag, .. } = &mut self;
-        *flag = is_template;
-        self
-    }
-}
+/// The status icon uses the same image formats as menu items.
+pub type TrayIcon = Icon;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrayMenuModel {
-    pub items: Vec<TrayMenuItem>,
+    pub items: Vec<MenuItem>,
 }
 
 impl TrayMenuModel {
-    pub fn new(items: Vec<TrayMenuItem>) -> Self {
+    pub fn new(items: Vec<MenuItem>) -> Self {
         Self { items }
     }
 }
 
-#[derive(Clone, Debug)]
-pub enum TrayMenuItem {
-    Command(TrayCommandItem),
-    Submenu(TraySubmenu),
-    Separator,
-}
-
-#[derive(Clone, Debug)]
-pub struct TrayCommandItem {
-    pub id: CommandId,
-    pub label: String,
-    pub enabled: bool,
-    pub checked: bool,
-    pub shortcut: Option<Shortcut>,
-    pub role: Option<TrayMenuItemRole>,
-}
-
-impl TrayCommandItem {
-    pub fn new(id: CommandId, label: impl Into<String>) -> Self {
-        Self {
-            id,
-            label: label.into(),
-            enabled: true,
-            checked: false,
-            shortcut: None,
-            role: None,
-        }
-    }
-
-    pub fn with_role(mut self, role: TrayMenuItemRole) -> Self {
-        self.role = Some(role);
-        self
+impl From<MenuModel> for TrayMenuModel {
+    fn from(model: MenuModel) -> Self {
+        Self::new(model.items)
     }
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
-pub enum TrayMenuItemRole {
-    About,
-    Preferences,
-    Services,
-    Hide,
-    HideOthers,
-    ShowAll,
-    Quit,
-    Minimize,
-    Zoom,
-    BringAllToFront,
-}
-
-#[derive(Clone, Debug)]
-pub struct TraySubmenu {
-    pub label: String,
-    pub items: Vec<TrayMenuItem>,
-}
-
-impl TraySubmenu {
-    pub fn new(label: impl Into<String>, items: Vec<TrayMenuItem>) -> Self {
-        Self {
-            label: label.into(),
-            items,
-        }
+impl From<TrayMenuModel> for MenuModel {
+    fn from(model: TrayMenuModel) -> Self {
+        MenuModel::new(model.items)
     }
 }
 
+// Tray menus share the item tree of context and app menus; the old names stay
+// available so existing tray code keeps compiling.
+pub type TrayMenuItem = MenuItem;
+pub type TrayCommandItem = CommandItem;
+pub type TraySubmenu = Submenu;
+pub type TrayMenuItemRole = MenuItemRole;
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrayModel {
     pub icon: TrayIcon,
     pub tooltip: Option<String>,
@@ -117,3 +57,42 @@ impl TrayModel {
         self
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::command::CommandId;
+
+    #[test]
+    fn tray_matches_golden_json() {
+        let model = TrayModel::new(
+            Icon::from_png_bytes([1, 2]).with_template(true),
+            TrayMenuModel::new(vec![MenuItem::separator()]),
+        )
+        .with_tooltip("Sync");
+        let golden = serde_json::json!({
+            "icon": {"png": {"bytes": [1, 2], "is_template": true}},
+            "tooltip": "Sync",
+            "menu": {"items": [{"separator": {"key": null}}]},
+        });
+        assert_eq!(serde_json::to_value(&model).unwrap(), golden);
+
+        let parsed: TrayModel = serde_json::from_value(golden).unwrap();
+        assert_eq!(parsed.icon, model.icon);
+        assert_eq!(parsed.tooltip, model.tooltip);
+        assert_eq!(MenuModel::from(parsed.menu), MenuModel::from(model.menu));
+    }
+
+    #[test]
+    fn tray_menu_reads_shorthand_items() {
+        let menu: TrayMenuModel =
+            serde_json::from_str(r#"{"items":[{"command":{"id":3,"label":"Open"}}]}"#).unwrap();
+        assert_eq!(
+            menu.items,
+            [MenuItem::Command(CommandItem::new(
+                CommandId::new(3).unwrap(),
+                "Open"
+            ))]
+        );
+    }
+}
@@ -1,16 +1,20 @@
 #![cfg(target_os = "macos")]
 
 use core::ffi::c_void;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
 use makepad_shell_core::command::CommandId;
 use makepad_shell_core::menu::*;
-use makepad_shell_core::shortcut::{Key, Shortcut};
+use makepad_shell_core::platform::Platform;
+use makepad_shell_core::shortcut::{Key, NumpadKey, Shortcut};
 use objc2::encode::{Encode, Encoding, RefEncode};
 use objc2::ffi::NSInteger;
 use objc2::rc::Retained;
-use objc2::runtime::{AnyObject, Bool, NSObject, Sel};
-use objc2::{class, define_class, msg_send, sel, DefinedClass, MainThreadMarker, MainThreadOnly};
+use objc2::runtime::{AnyClass, AnyObject, Bool, NSObject, Sel};
+use objc2::{
+    ClassType, DefinedClass, MainThreadMarker, MainThreadOnly, class, define_class, msg_send, sel,
+};
 
 #[derive(Debug)]
 pub enum MacMenuError {
@@ -22,19 +26,38 @@ pub enum MacMenuError {
 pub fn popup_context_menu_macos(
     menu: MenuModel,
     anchor: MenuAnchor,
-    _trigger: MenuTrigger,
+    trigger: MenuTrigger,
     ns_view: *mut c_void,
     ns_event: *mut c_void,
     on_command: Box<dyn Fn(CommandId) + 'static>,
+) -> Result<(), MacMenuError> {
+    popup_context_menu_macos_with_events(
+        menu,
+        anchor,
+        trigger,
+        ns_view,
+        ns_event,
+        command_events(on_command),
+        None,
+    )
+}
+
+pub fn popup_context_menu_macos_with_events(
+    menu: MenuModel,
+    anchor: MenuAnchor,
+    _trigger: MenuTrigger,
+    ns_view: *mut c_void,
+    ns_event: *mut c_void,
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+    state: Option<Box<dyn CommandStateProvider + 'static>>,
 ) -> Result<(), MacMenuError> {
     let mtm = main_thread_marker();
 
-    let target = MenuTarget::new(on_command, mtm);
+    let events = MenuEvents::new(on_event);
+    let validates = state.is_some();
+    let target = MenuTarget::new(events.clone(), state, false, mtm);
     let target_ptr = Retained::as_ptr(&target) as *mut AnyObject;
-    let ctx = BuildContext {
-        target: Some(target_ptr),
-    };
-    let menu = build_ns_menu_items(&menu.items, &ctx)?;
+    let (menu, _delegate) = build_ns_menu_with_target(&menu.items, target_ptr, &events, validates)?;
 
     unsafe { popup_menu(menu, anchor, ns_view, ns_event) }?;
     Ok(())
@@ -43,10 +66,20 @@ pub fn popup_context_menu_macos(
 pub fn set_app_menu_macos(
     menu_bar: MenuBarModel,
     on_command: Box<dyn Fn(CommandId) + 'static>,
+) -> Result<(), MacMenuError> {
+    set_app_menu_macos_with_events(menu_bar, command_events(on_command), None)
+}
+
+pub fn set_app_menu_macos_with_events(
+    menu_bar: MenuBarModel,
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+    state: Option<Box<dyn CommandStateProvider + 'static>>,
 ) -> Result<(), MacMenuError> {
     let mtm = main_thread_marker();
 
-    let target = MenuTarget::new(on_command, mtm);
+    let events = MenuEvents::new(on_event);
+    let validates = state.is_some();
+    let target = MenuTarget::new(events.clone(), state, true, mtm);
     let target_ptr = Retained::as_ptr(&target) as *mut AnyObject;
     APP_MENU_TARGET.with(|slot| {
         *slot.borrow_mut() = Some(target);
@@ -54,7 +87,47 @@ pub fn set_app_menu_macos(
 
     let ctx = BuildContext {
         target: Some(target_ptr),
+        events: Some(events),
+        validates,
     };
+    install_main_menu(menu_bar, &ctx)
+}
+
+/// Replaces the app menu's model, keeping the callbacks and state provider
+/// it was set with. Unchanged items stay as they are. Does nothing if no app
+/// menu is set.
+pub fn update_app_menu_macos(menu_bar: MenuBarModel) -> Result<(), MacMenuError> {
+    match app_menu_context() {
+        Some(ctx) => install_main_menu(menu_bar, &ctx),
+        None => Ok(()),
+    }
+}
+
+/// Sets the titles of the About and Quit items of the app menu put in front
+/// of menu bars without a `TopMenuRole::App` menu, e.g. to localized ones.
+/// `None` is "About <app>" or "Quit <app>" with the process name. Menu bars
+/// installed afterwards use them.
+pub fn set_default_app_menu_titles_macos(about: Option<String>, quit: Option<String>) {
+    DEFAULT_APP_MENU_TITLES.with(|titles| *titles.borrow_mut() = (about, quit));
+}
+
+/// The process name, which the default app menu's titles include.
+pub fn process_name_macos() -> String {
+    nsstring_to_string(process_name_nsstring()).unwrap_or_default()
+}
+
+/// The context the current app menu was built with.
+fn app_menu_context() -> Option<BuildContext> {
+    APP_MENU_TARGET.with(|slot| {
+        slot.borrow().as_ref().map(|target| BuildContext {
+            target: Some(Retained::as_ptr(target) as *mut AnyObject),
+            events: Some(target.ivars().events.clone()),
+            validates: target.ivars().state.is_some(),
+        })
+    })
+}
+
+fn install_main_menu(menu_bar: MenuBarModel, ctx: &BuildContext) -> Result<(), MacMenuError> {
     let normalized = normalize_menu_bar(menu_bar);
     let has_app = has_app_menu(&normalized);
 
@@ -62,9 +135,9 @@ pub fn set_app_menu_macos(
         let current_menu = current_main_menu();
         let updated = APP_MENU_STATE.with(|slot| {
             if let Some(state) = slot.borrow_mut().as_mut() {
-                if state.menu == current_menu {
+                if state.menu == current_menu && !state.default_app_menu {
                     if let Ok(true) =
-                        try_update_main_menu(state.menu, &state.model, &normalized, &ctx)
+                        try_update_main_menu(state.menu, &state.model, &normalized, ctx)
                     {
                         state.model = normalized.clone();
                         return true;
@@ -78,7 +151,15 @@ pub fn set_app_menu_macos(
         }
     }
 
-    let main_menu = build_main_menu(&normalized, &ctx, has_app)?;
+    replace_main_menu(normalized, ctx, has_app)
+}
+
+fn replace_main_menu(
+    normalized: MenuBarModel,
+    ctx: &BuildContext,
+    has_app: bool,
+) -> Result<(), MacMenuError> {
+    let main_menu = build_main_menu(&normalized, ctx, has_app)?;
 
     unsafe {
         let ns_app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
@@ -86,52 +167,224 @@ pub fn set_app_menu_macos(
     }
 
     APP_MENU_STATE.with(|slot| {
-        if has_app {
-            *slot.borrow_mut() = Some(AppMenuState {
-                model: normalized,
-                menu: main_menu,
-            });
-        } else {
-            *slot.borrow_mut() = None;
-        }
+        *slot.borrow_mut() = Some(AppMenuState {
+            model: normalized,
+            menu: main_menu,
+            default_app_menu: !has_app,
+        });
     });
 
     Ok(())
 }
 
+/// Runs `update` on the app menu's items of command `id` and changes only
+/// those native items, or rebuilds the menu bar if a change does not fit.
+/// Does nothing if no app menu is set.
+pub fn update_app_menu_command_macos(
+    id: CommandId,
+    mut update: impl FnMut(&mut CommandItem),
+) -> Result<(), MacMenuError> {
+    let rebuild = APP_MENU_STATE.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let state = slot.as_mut()?;
+        let mut model = state.model.clone();
+        let mut patched = true;
+        for (index, menu) in model.menus.iter_mut().enumerate() {
+            let ops = update_command_items(&mut menu.items, id, &mut update);
+            let submenu = state.ns_top_menu(index);
+            if patched && !submenu.is_null() {
+                patched = matches!(apply_ns_menu_ops(submenu, &ops, &menu.items), Ok(true));
+            }
+        }
+        if patched {
+            state.model = model;
+            return None;
+        }
+        Some((model, !state.default_app_menu))
+    });
+    match rebuild {
+        Some((model, has_app)) => {
+            let ctx = app_menu_context().ok_or(MacMenuError::Unsupported)?;
+            replace_main_menu(model, &ctx, has_app)
+        }
+        None => Ok(()),
+    }
+}
+
 pub fn build_ns_menu_from_model(menu: &MenuModel) -> Result<*mut AnyObject, MacMenuError> {
-    let ctx = BuildContext { target: None };
+    let ctx = BuildContext {
+        target: None,
+        events: None,
+        validates: false,
+    };
     build_ns_menu_items(&menu.items, &ctx)
 }
 
-#[allow(dead_code)]
+/// Builds a context or tray menu. The menu only holds its delegate weakly,
+/// so the caller keeps it for as long as the menu is used.
 pub(crate) fn build_ns_menu_with_target(
     items: &[MenuItem],
     target: *mut AnyObject,
-) -> Result<*mut AnyObject, MacMenuError> {
+    events: &Rc<MenuEvents>,
+    validates: bool,
+) -> Result<(*mut AnyObject, Retained<MenuDelegate>), MacMenuError> {
     let ctx = BuildContext {
         target: Some(target),
+        events: Some(events.clone()),
+        validates,
     };
-    build_ns_menu_items(items, &ctx)
+    let menu = build_ns_menu_items(items, &ctx)?;
+    let delegate = MenuDelegate::new(MenuKind::Popup, None, &ctx, main_thread_marker());
+    unsafe {
+        let _: () = msg_send![menu, setDelegate: Retained::as_ptr(&delegate) as *mut AnyObject];
+    }
+    Ok((menu, delegate))
 }
 
-#[allow(dead_code)]
 pub(crate) fn try_update_ns_menu(
     menu: *mut AnyObject,
-    old: &MenuModel,
-    new: &MenuModel,
+    old: &[MenuItem],
+    new: &[MenuItem],
     target: *mut AnyObject,
+    events: &Rc<MenuEvents>,
+    validates: bool,
 ) -> Result<bool, MacMenuError> {
     let ctx = BuildContext {
         target: Some(target),
+        events: Some(events.clone()),
+        validates,
     };
-    if !menu_items_shape_eq(&old.items, &new.items) {
-        return Ok(false);
+    let ops = diff_menu_items(old, new);
+    for op in &ops {
+        if !apply_ns_menu_op(menu, op, &ctx, |path| menu_item_at(new, path))? {
+            return Ok(false);
+        }
     }
-    update_menu_items(menu, &old.items, &new.items, &ctx)?;
+    retarget_ns_menu(menu, &ctx);
     Ok(true)
 }
 
+/// Runs `auto_check_menu_items` on `items` and mirrors the new check marks
+/// into `menu`, which must be the menu built from `items`.
+pub(crate) fn auto_check_ns_menu(menu: *mut AnyObject, items: &mut [MenuItem], id: CommandId) {
+    let ctx = BuildContext {
+        target: None,
+        events: None,
+        validates: false,
+    };
+    for op in &auto_check_menu_items(items, id) {
+        let _ = apply_ns_menu_op(menu, op, &ctx, |_| None);
+    }
+}
+
+/// Runs `update_command_items` on `items` and mirrors the changes into
+/// `menu`, which must be the menu built from `items`. Returns `false` if a
+/// change did not fit the native menu; `items` are updated either way.
+pub(crate) fn update_ns_menu_command(
+    menu: *mut AnyObject,
+    items: &mut [MenuItem],
+    id: CommandId,
+    update: impl FnMut(&mut CommandItem),
+) -> Result<bool, MacMenuError> {
+    let ops = update_command_items(items, id, update);
+    apply_ns_menu_ops(menu, &ops, items)
+}
+
+/// Applies `ops` that change items in place to `menu`, built from the
+/// items `items` replaced. Returns `false` if an op did not fit, including
+/// any op that needs a new native item: without the menu's target it could
+/// not be invoked.
+fn apply_ns_menu_ops(
+    menu: *mut AnyObject,
+    ops: &[MenuPatchOp],
+    items: &[MenuItem],
+) -> Result<bool, MacMenuError> {
+    let ctx = BuildContext {
+        target: None,
+        events: None,
+        validates: false,
+    };
+    for op in ops {
+        let builds_item = matches!(
+            op,
+            MenuPatchOp::Insert { .. } | MenuPatchOp::SetProvider { .. }
+        );
+        if builds_item || !apply_ns_menu_op(menu, op, &ctx, |path| menu_item_at(items, path))? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Where a menu's events go; shared by its target and its delegates.
+pub(crate) struct MenuEvents {
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+    invoked: Cell<bool>,
+}
+
+impl MenuEvents {
+    pub(crate) fn new(on_event: Box<dyn Fn(MenuEvent) + 'static>) -> Rc<Self> {
+        Rc::new(Self {
+            on_event,
+            invoked: Cell::new(false),
+        })
+    }
+
+    pub(crate) fn emit(&self, event: MenuEvent) {
+        match event {
+            MenuEvent::Opened => self.invoked.set(false),
+            MenuEvent::CommandInvoked(_) => self.invoked.set(true),
+            _ => {}
+        }
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            (self.on_event)(event);
+        }));
+    }
+}
+
+/// Applies `state`'s answer for `item`'s command and returns whether the
+/// item is enabled, for `validateMenuItem:`.
+pub(crate) fn validate_ns_menu_item(
+    item: &AnyObject,
+    state: Option<&dyn CommandStateProvider>,
+) -> Bool {
+    unsafe {
+        let enabled: Bool = msg_send![item, isEnabled];
+        let tag: NSInteger = msg_send![item, tag];
+        let (Some(state), Some(cmd)) = (state, tag_to_command_id(tag)) else {
+            return enabled;
+        };
+        let Ok(answer) =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| state.command_state(cmd)))
+        else {
+            return enabled;
+        };
+        if let Some(label) = answer.label {
+            // Keep a chord shown after the tab.
+            let old = nsstring_to_string(msg_send![item, title]).unwrap_or_default();
+            let mut title = display_label(&label, None);
+            if let Some((_, chord)) = old.split_once('\t') {
+                title = format!("{title}\t{chord}");
+            }
+            let _: () = msg_send![item, setTitle: nsstring(&title)];
+        }
+        if let Some(checked) = answer.checked {
+            let _: () = msg_send![item, setState: control_state(checked)];
+        }
+        answer.enabled.map_or(enabled, Bool::new)
+    }
+}
+
+pub(crate) fn command_events(
+    on_command: Box<dyn Fn(CommandId) + 'static>,
+) -> Box<dyn Fn(MenuEvent) + 'static> {
+    Box::new(move |event| {
+        if let Some(cmd) = event.command() {
+            on_command(cmd);
+        }
+    })
+}
+
 // ------------------------------
 // Internal helpers
 // ------------------------------
@@ -143,14 +396,29 @@ thread_local! {
 struct AppMenuState {
     model: MenuBarModel,
     menu: *mut AnyObject,
+    /// A default app menu was put in front of the model's menus.
+    default_app_menu: bool,
+}
+
+impl AppMenuState {
+    /// The native menu of the model's top-level menu `index`, or null.
+    fn ns_top_menu(&self, index: usize) -> *mut AnyObject {
+        ns_menu_at(self.menu, &[index + self.default_app_menu as usize])
+    }
 }
 
 thread_local! {
     static APP_MENU_STATE: RefCell<Option<AppMenuState>> = RefCell::new(None);
+    /// Titles of the default app menu's About and Quit items.
+    static DEFAULT_APP_MENU_TITLES: RefCell<(Option<String>, Option<String>)> =
+        const { RefCell::new((None, None)) };
 }
 
 struct BuildContext {
     target: Option<*mut AnyObject>,
+    events: Option<Rc<MenuEvents>>,
+    /// The target answers `validateMenuItem:` from a `CommandStateProvider`.
+    validates: bool,
 }
 
 impl BuildContext {
@@ -169,7 +437,7 @@ fn build_ns_menu_items(items: &[MenuItem], ctx: &BuildContext) -> Result<*mut An
         let menu: *mut AnyObject = msg_send![class!(NSMenu), alloc];
         let title = nsstring("");
         let menu: *mut AnyObject = msg_send![menu, initWithTitle: title];
-        let _: () = msg_send![menu, setAutoenablesItems: false];
+        let _: () = msg_send![menu, setAutoenablesItems: ctx.validates];
 
         for item in items {
             if let Some(mi) = build_ns_menu_item(item, ctx)? {
@@ -201,15 +469,9 @@ fn build_main_menu(
         }
 
         for menu in &menu_bar.menus {
-            let title = if menu.role == Some(TopMenuRole::App) {
-                process_name_nsstring()
-            } else if menu.label.is_empty() {
-                process_name_nsstring()
-            } else {
-                nsstring(&menu.label)
-            };
-            let root = new_menu_item(title, None, nsstring(""));
+            let root = new_menu_item(top_menu_title(&menu.label), None, nsstring(""));
             let submenu = build_ns_menu_items(&menu.items, ctx)?;
+            set_ns_menu_delegate(root, submenu, MenuKind::Submenu, None, ctx);
             let _: () = msg_send![root, setSubmenu: submenu];
             let _: () = msg_send![main_menu, addItem: root];
         }
@@ -224,7 +486,7 @@ fn build_ns_menu_item(
 ) -> Result<Option<*mut AnyObject>, MacMenuError> {
     unsafe {
         match item {
-            MenuItem::Separator => {
+            MenuItem::Separator(_) => {
                 // [NSMenuItem separatorItem]
                 let sep: *mut AnyObject = msg_send![class!(NSMenuItem), separatorItem];
                 Ok(Some(sep))
@@ -232,31 +494,22 @@ fn build_ns_menu_item(
 
             MenuItem::Command(cmd) => {
                 // [[NSMenuItem alloc] initWithTitle:action:keyEquivalent:]
-                let title = nsstring(&cmd.label);
+                let title = nsstring(&command_title(cmd));
                 let action = if let Some(role) = cmd.role {
                     role_selector(role)
                 } else {
                     ctx.action()
                 };
-                let mut key_equiv = nsstring("");
-                let mut key_mods: Option<u64> = None;
-                if let Some(shortcut) = cmd.shortcut {
-                    if let Some((equiv, mods)) = shortcut_to_key_equivalent(shortcut) {
-                        key_equiv = nsstring(&equiv);
-                        key_mods = Some(mods);
-                    }
-                } else if let Some(role) = cmd.role {
-                    let (equiv, mods) = role_key_equivalent_with_mods(role);
-                    if !equiv.is_empty() {
-                        key_equiv = nsstring(equiv);
-                        key_mods = Some(mods);
-                    }
-                }
+                let key_equiv = command_key_equivalent(cmd);
+                let key_mods = key_equiv.as_ref().map(|(_, mods)| *mods);
+                let key_equiv = nsstring(key_equiv.as_ref().map_or("", |(equiv, _)| equiv));
                 let mi = new_menu_item(title, action, key_equiv);
 
                 if let Some(target) = ctx.target {
                     let _: () = msg_send![mi, setTarget: target];
                 }
+                set_ns_item_image(mi, cmd.icon.as_ref());
+                set_ns_item_badge(mi, cmd.badge.as_deref());
                 if let Some(mods) = key_mods {
                     let _: () = msg_send![mi, setKeyEquivalentModifierMask: mods];
                 }
@@ -267,11 +520,14 @@ fn build_ns_menu_item(
                 }
 
                 let _: () = msg_send![mi, setEnabled: cmd.enabled];
+                // Hidden items keep their key equivalent.
+                let _: () = msg_send![mi, setHidden: !cmd.visible];
+                if cmd.alternate_of.is_some() {
+                    let _: () = msg_send![mi, setAlternate: true];
+                }
 
-                // checked -> state (0 off, 1 on)
-                // NSControlStateValueOff = 0, On = 1
-                let state: NSInteger = if cmd.checked { 1 } else { 0 };
-                let _: () = msg_send![mi, setState: state];
+                // Radio items use the same check mark; AppKit has no radio style.
+                let _: () = msg_send![mi, setState: control_state(cmd.checked)];
 
                 let tag = command_id_to_tag(cmd.id)?;
                 let _: () = msg_send![mi, setTag: tag];
@@ -282,10 +538,13 @@ fn build_ns_menu_item(
             MenuItem::Submenu(sub) => {
                 // Create submenu item:
                 // NSMenuItem *root = [[NSMenuItem alloc] initWithTitle:action:keyEquivalent:]
-                let title = nsstring(&sub.label);
+                let title = nsstring(&display_label(&sub.label, sub.badge.as_deref()));
                 let root = new_menu_item(title, None, nsstring(""));
+                set_ns_item_image(root, sub.icon.as_ref());
+                set_ns_item_badge(root, sub.badge.as_deref());
 
                 let submenu = build_ns_menu_items(&sub.items, ctx)?;
+                set_ns_menu_delegate(root, submenu, MenuKind::Submenu, sub.provider.as_ref(), ctx);
                 let _: () = msg_send![root, setSubmenu: submenu];
 
                 Ok(Some(root))
@@ -294,6 +553,15 @@ fn build_ns_menu_item(
     }
 }
 
+fn control_state(checked: CheckState) -> NSInteger {
+    // NSControlStateValueMixed = -1, Off = 0, On = 1
+    match checked {
+        CheckState::Off => 0,
+        CheckState::On => 1,
+        CheckState::Mixed => -1,
+    }
+}
+
 fn command_id_to_tag(id: CommandId) -> Result<NSInteger, MacMenuError> {
     let id_u64 = id.as_u64();
     if id_u64 > (isize::MAX as u64) {
@@ -394,177 +662,249 @@ fn normalize_app_menu_items(items: Vec<MenuItem>) -> Vec<MenuItem> {
     let mut out = Vec::new();
     for section in sections {
         if !out.is_empty() {
-            out.push(MenuItem::Separator);
+            out.push(MenuItem::separator());
         }
         out.extend(section);
     }
     out
 }
 
-fn menu_bar_shape_eq(old: &MenuBarModel, new: &MenuBarModel) -> bool {
-    if old.menus.len() != new.menus.len() {
-        return false;
-    }
-    for (old_menu, new_menu) in old.menus.iter().zip(new.menus.iter()) {
-        if old_menu.role != new_menu.role {
-            return false;
-        }
-        if !menu_items_shape_eq(&old_menu.items, &new_menu.items) {
-            return false;
-        }
-    }
-    true
-}
-
-fn menu_items_shape_eq(old_items: &[MenuItem], new_items: &[MenuItem]) -> bool {
-    if old_items.len() != new_items.len() {
-        return false;
-    }
-    for (old_item, new_item) in old_items.iter().zip(new_items.iter()) {
-        if !menu_item_shape_eq(old_item, new_item) {
-            return false;
-        }
-    }
-    true
-}
-
-fn menu_item_shape_eq(old_item: &MenuItem, new_item: &MenuItem) -> bool {
-    match (old_item, new_item) {
-        (MenuItem::Separator, MenuItem::Separator) => true,
-        (MenuItem::Command(old_cmd), MenuItem::Command(new_cmd)) => old_cmd.role == new_cmd.role,
-        (MenuItem::Submenu(old_sub), MenuItem::Submenu(new_sub)) => {
-            menu_items_shape_eq(&old_sub.items, &new_sub.items)
-        }
-        _ => false,
-    }
-}
-
 fn try_update_main_menu(
     menu: *mut AnyObject,
     old: &MenuBarModel,
     new: &MenuBarModel,
     ctx: &BuildContext,
 ) -> Result<bool, MacMenuError> {
-    if !menu_bar_shape_eq(old, new) {
+    let Some(ops) = diff_menu_bar(old, new) else {
         return Ok(false);
-    }
+    };
     unsafe {
-        for (index, (old_menu, new_menu)) in old.menus.iter().zip(new.menus.iter()).enumerate() {
+        for op in &ops {
+            match op {
+                MenuPatchOp::SetLabel { path, label } if path.len() == 1 => {
+                    let item: *mut AnyObject = msg_send![menu, itemAtIndex: path[0] as NSInteger];
+                    if item.is_null() {
+                        return Ok(false);
+                    }
+                    let _: () = msg_send![item, setTitle: top_menu_title(label)];
+                }
+                op => {
+                    let applied = apply_ns_menu_op(menu, op, ctx, |path| {
+                        let (first, rest) = path.split_first()?;
+                        menu_item_at(&new.menus.get(*first)?.items, rest)
+                    })?;
+                    if !applied {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+    }
+    retarget_ns_menu(menu, ctx);
+    Ok(true)
+}
+
+/// The submenu at `path` below `menu`, or null.
+fn ns_menu_at(menu: *mut AnyObject, path: &[usize]) -> *mut AnyObject {
+    let mut menu = menu;
+    for &index in path {
+        unsafe {
             let item: *mut AnyObject = msg_send![menu, itemAtIndex: index as NSInteger];
             if item.is_null() {
-                return Ok(false);
-            }
-            if new_menu.role == Some(TopMenuRole::App) || old_menu.label != new_menu.label {
-                let title = if new_menu.role == Some(TopMenuRole::App) {
-                    process_name_nsstring()
-                } else if new_menu.label.is_empty() {
-                    process_name_nsstring()
-                } else {
-                    nsstring(&new_menu.label)
-                };
-                let _: () = msg_send![item, setTitle: title];
+                return std::ptr::null_mut();
             }
-            let submenu: *mut AnyObject = msg_send![item, submenu];
-            if submenu.is_null() {
-                return Ok(false);
-            }
-            update_menu_items(submenu, &old_menu.items, &new_menu.items, ctx)?;
+            menu = msg_send![item, submenu];
+        }
+        if menu.is_null() {
+            break;
         }
     }
-    Ok(true)
+    menu
+}
+
+fn ns_menu_item_at(menu: *mut AnyObject, path: &[usize]) -> *mut AnyObject {
+    let Some((&index, parent)) = path.split_last() else {
+        return std::ptr::null_mut();
+    };
+    let menu = ns_menu_at(menu, parent);
+    if menu.is_null() {
+        return std::ptr::null_mut();
+    }
+    unsafe { msg_send![menu, itemAtIndex: index as NSInteger] }
 }
 
-fn update_menu_items(
+/// Applies one patch step to the native menu. `new_item` looks up the item a
+/// step refers to in the new model. Returns `false` when the menu no longer
+/// matches the model the patch was computed from.
+fn apply_ns_menu_op<'a>(
     menu: *mut AnyObject,
-    old_items: &[MenuItem],
-    new_items: &[MenuItem],
+    op: &MenuPatchOp,
     ctx: &BuildContext,
-) -> Result<(), MacMenuError> {
+    new_item: impl Fn(&[usize]) -> Option<&'a MenuItem>,
+) -> Result<bool, MacMenuError> {
     unsafe {
-        for (index, (old_item, new_item)) in old_items.iter().zip(new_items.iter()).enumerate() {
-            let item: *mut AnyObject = msg_send![menu, itemAtIndex: index as NSInteger];
-            if item.is_null() {
-                return Ok(());
+        match op {
+            MenuPatchOp::Remove { parent, index } => {
+                let parent = ns_menu_at(menu, parent);
+                if parent.is_null() {
+                    return Ok(false);
+                }
+                let _: () = msg_send![parent, removeItemAtIndex: *index as NSInteger];
+            }
+            MenuPatchOp::Insert {
+                parent,
+                index,
+                item,
+            } => {
+                let parent = ns_menu_at(menu, parent);
+                if parent.is_null() {
+                    return Ok(false);
+                }
+                if let Some(mi) = build_ns_menu_item(item, ctx)? {
+                    let _: () = msg_send![parent, insertItem: mi, atIndex: *index as NSInteger];
+                }
+            }
+            MenuPatchOp::Move { parent, from, to } => {
+                let parent = ns_menu_at(menu, parent);
+                if parent.is_null() {
+                    return Ok(false);
+                }
+                let item: *mut AnyObject = msg_send![parent, itemAtIndex: *from as NSInteger];
+                // Keep the item alive while it is out of the menu.
+                let Some(item) = Retained::retain(item) else {
+                    return Ok(false);
+                };
+                let item = Retained::as_ptr(&item) as *mut AnyObject;
+                let _: () = msg_send![parent, removeItemAtIndex: *from as NSInteger];
+                let _: () = msg_send![parent, insertItem: item, atIndex: *to as NSInteger];
             }
-            match (old_item, new_item) {
-                (MenuItem::Separator, MenuItem::Separator) => {}
-                (MenuItem::Command(old_cmd), MenuItem::Command(new_cmd)) => {
-                    update_command_item(item, old_cmd, new_cmd, ctx)?;
+            MenuPatchOp::SetLabel { path, .. }
+            | MenuPatchOp::SetShortcut { path, .. }
+            | MenuPatchOp::SetBadge { path, .. } => {
+                let item = ns_menu_item_at(menu, path);
+                if item.is_null() {
+                    return Ok(false);
                 }
-                (MenuItem::Submenu(old_sub), MenuItem::Submenu(new_sub)) => {
-                    if old_sub.label != new_sub.label {
-                        let title = nsstring(&new_sub.label);
+                match new_item(path) {
+                    Some(MenuItem::Command(cmd)) => {
+                        let title = nsstring(&command_title(cmd));
                         let _: () = msg_send![item, setTitle: title];
+                        set_ns_item_badge(item, cmd.badge.as_deref());
+                        let (equiv, mods) = command_key_equivalent(cmd).unwrap_or_default();
+                        let key_equiv = nsstring(&equiv);
+                        let _: () = msg_send![item, setKeyEquivalent: key_equiv];
+                        let _: () = msg_send![item, setKeyEquivalentModifierMask: mods];
                     }
-                    let submenu: *mut AnyObject = msg_send![item, submenu];
-                    if !submenu.is_null() {
-                        update_menu_items(submenu, &old_sub.items, &new_sub.items, ctx)?;
+                    Some(MenuItem::Submenu(sub)) => {
+                        let title = nsstring(&display_label(&sub.label, sub.badge.as_deref()));
+                        let _: () = msg_send![item, setTitle: title];
+                        set_ns_item_badge(item, sub.badge.as_deref());
                     }
+                    _ => return Ok(false),
+                }
+            }
+            MenuPatchOp::SetIcon { path, icon } => {
+                let item = ns_menu_item_at(menu, path);
+                if item.is_null() {
+                    return Ok(false);
+                }
+                set_ns_item_image(item, icon.as_ref());
+            }
+            MenuPatchOp::SetCommandId { path, id } => {
+                let item = ns_menu_item_at(menu, path);
+                if item.is_null() {
+                    return Ok(false);
+                }
+                let tag = command_id_to_tag(*id)?;
+                let _: () = msg_send![item, setTag: tag];
+            }
+            MenuPatchOp::SetEnabled { path, enabled } => {
+                let item = ns_menu_item_at(menu, path);
+                if item.is_null() {
+                    return Ok(false);
+                }
+                let _: () = msg_send![item, setEnabled: *enabled];
+            }
+            MenuPatchOp::SetProvider { path, .. } => {
+                // The delegate owns the provider; swap in a fresh item.
+                let Some((&index, parent)) = path.split_last() else {
+                    return Ok(false);
+                };
+                let parent = ns_menu_at(menu, parent);
+                let Some(new) = new_item(path) else {
+                    return Ok(false);
+                };
+                if parent.is_null() {
+                    return Ok(false);
+                }
+                let _: () = msg_send![parent, removeItemAtIndex: index as NSInteger];
+                if let Some(mi) = build_ns_menu_item(new, ctx)? {
+                    let _: () = msg_send![parent, insertItem: mi, atIndex: index as NSInteger];
                 }
-                _ => {}
+            }
+            MenuPatchOp::SetVisible { path, visible } => {
+                let item = ns_menu_item_at(menu, path);
+                if item.is_null() {
+                    return Ok(false);
+                }
+                let _: () = msg_send![item, setHidden: !*visible];
+            }
+            MenuPatchOp::SetChecked { path, checked } => {
+                let item = ns_menu_item_at(menu, path);
+                if item.is_null() {
+                    return Ok(false);
+                }
+                let _: () = msg_send![item, setState: control_state(*checked)];
             }
         }
     }
-    Ok(())
+    Ok(true)
 }
 
-fn update_command_item(
-    item: *mut AnyObject,
-    old_cmd: &CommandItem,
-    new_cmd: &CommandItem,
-    ctx: &BuildContext,
-) -> Result<(), MacMenuError> {
+// Every update brings a new target, so untouched items are pointed at it too.
+fn retarget_ns_menu(menu: *mut AnyObject, ctx: &BuildContext) {
     unsafe {
-        let action = if let Some(role) = new_cmd.role {
-            role_selector(role)
-        } else {
-            ctx.action()
-        };
-        let _: () = msg_send![item, setAction: action];
-        if let Some(target) = ctx.target {
-            let _: () = msg_send![item, setTarget: target];
+        if MenuDelegate::of(menu).is_some() {
+            let _: () = msg_send![menu, setAutoenablesItems: ctx.validates];
         }
-        else {
-            let _: () = msg_send![item, setTarget: std::ptr::null::<AnyObject>()];
-        }
-
-        let mut key_equiv = nsstring("");
-        let mut key_mods: Option<u64> = None;
-        if let Some(shortcut) = new_cmd.shortcut {
-            if let Some((equiv, mods)) = shortcut_to_key_equivalent(shortcut) {
-                key_equiv = nsstring(&equiv);
-                key_mods = Some(mods);
+        let count: NSInteger = msg_send![menu, numberOfItems];
+        for index in 0..count {
+            let item: *mut AnyObject = msg_send![menu, itemAtIndex: index];
+            if item.is_null() {
+                continue;
+            }
+            let tag: NSInteger = msg_send![item, tag];
+            if tag > 0 {
+                let target = ctx.target.unwrap_or(std::ptr::null_mut());
+                let _: () = msg_send![item, setTarget: target];
+                continue;
             }
-        } else if let Some(role) = new_cmd.role {
-            let (equiv, mods) = role_key_equivalent_with_mods(role);
-            if !equiv.is_empty() {
-                key_equiv = nsstring(equiv);
-                key_mods = Some(mods);
+            let submenu: *mut AnyObject = msg_send![item, submenu];
+            if !submenu.is_null() {
+                if let Some(delegate) = MenuDelegate::of(submenu) {
+                    delegate.retarget(ctx);
+                }
+                retarget_ns_menu(submenu, ctx);
             }
         }
-        let _: () = msg_send![item, setKeyEquivalent: key_equiv];
-        let _: () = msg_send![item, setKeyEquivalentModifierMask: key_mods.unwrap_or(0)];
+    }
+}
 
-        if old_cmd.label != new_cmd.label {
-            let title = nsstring(&new_cmd.label);
-            let _: () = msg_send![item, setTitle: title];
-        }
-        if old_cmd.enabled != new_cmd.enabled {
-            let _: () = msg_send![item, setEnabled: new_cmd.enabled];
-        }
-        if old_cmd.checked != new_cmd.checked {
-            let state: NSInteger = if new_cmd.checked { 1 } else { 0 };
-            let _: () = msg_send![item, setState: state];
-        }
-        if old_cmd.id != new_cmd.id {
-            let tag = command_id_to_tag(new_cmd.id)?;
-            let _: () = msg_send![item, setTag: tag];
-        }
-        if new_cmd.role == Some(MenuItemRole::Services) {
-            let _ = build_services_menu();
+// Context menus are dismissed on invoke, so only the app menu keeps the marks.
+fn auto_check_app_menu(cmd: CommandId) {
+    APP_MENU_STATE.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let Some(state) = slot.as_mut() else {
+            return;
+        };
+        for index in 0..state.model.menus.len() {
+            let submenu = state.ns_top_menu(index);
+            if submenu.is_null() {
+                continue;
+            }
+            auto_check_ns_menu(submenu, &mut state.model.menus[index].items, cmd);
         }
-    }
-    Ok(())
+    });
 }
 
 fn main_thread_marker() -> MainThreadMarker {
@@ -586,14 +926,21 @@ fn build_default_app_menu() -> Result<*mut AnyObject, MacMenuError> {
         let _: () = msg_send![menu, setAutoenablesItems: false];
 
         let app_name = process_name_nsstring();
-        let about_title = string_by_appending("About ", app_name);
+        let (about, quit) = DEFAULT_APP_MENU_TITLES.with(|titles| titles.borrow().clone());
+        let about_title = match about {
+            Some(title) => nsstring(&title),
+            None => string_by_appending("About ", app_name),
+        };
         let about_item = new_menu_item(about_title, Some(sel!(orderFrontStandardAboutPanel:)), nsstring(""));
         let _: () = msg_send![menu, addItem: about_item];
 
         let sep: *mut AnyObject = msg_send![class!(NSMenuItem), separatorItem];
         let _: () = msg_send![menu, addItem: sep];
 
-        let quit_title = string_by_appending("Quit ", app_name);
+        let quit_title = match quit {
+            Some(title) => nsstring(&title),
+            None => string_by_appending("Quit ", app_name),
+        };
         let quit_item = new_menu_item(quit_title, Some(sel!(terminate:)), nsstring("q"));
         let _: () = msg_send![menu, addItem: quit_item];
 
@@ -612,6 +959,15 @@ fn build_services_menu() -> Result<*mut AnyObject, MacMenuError> {
     }
 }
 
+// The app menu's label may be localized, so it wins over the process name.
+fn top_menu_title(label: &str) -> *mut AnyObject {
+    if label.is_empty() {
+        process_name_nsstring()
+    } else {
+        nsstring(&MnemonicLabel::parse(label).text)
+    }
+}
+
 fn process_name_nsstring() -> *mut AnyObject {
     unsafe {
         let info: *mut AnyObject = msg_send![class!(NSProcessInfo), processInfo];
@@ -652,22 +1008,115 @@ fn role_selector(role: MenuItemRole) -> Option<Sel> {
     }
 }
 
-fn role_key_equivalent_with_mods(role: MenuItemRole) -> (&'static str, u64) {
-    match role {
-        MenuItemRole::About => ("", 0),
-        MenuItemRole::Preferences => (",", MOD_COMMAND),
-        MenuItemRole::Services => ("", 0),
-        MenuItemRole::Hide => ("h", MOD_COMMAND),
-        MenuItemRole::HideOthers => ("h", MOD_COMMAND | MOD_OPTION),
-        MenuItemRole::ShowAll => ("", 0),
-        MenuItemRole::Quit => ("q", MOD_COMMAND),
-        MenuItemRole::Minimize => ("m", MOD_COMMAND),
-        MenuItemRole::Zoom => ("", 0),
-        MenuItemRole::BringAllToFront => ("", 0),
+fn command_key_equivalent(cmd: &CommandItem) -> Option<(String, u64)> {
+    let equivalent = match cmd.shortcut {
+        Some(sequence) => sequence.as_single().and_then(shortcut_to_key_equivalent),
+        None => cmd
+            .role
+            .filter(|_| cmd.role_shortcut)
+            .and_then(role_key_equivalent_with_mods),
+    };
+    // An alternate without a shortcut shows while Option alone is held.
+    match equivalent {
+        None if cmd.alternate_of.is_some() => Some((String::new(), MOD_OPTION)),
+        equivalent => equivalent,
+    }
+}
+
+fn role_key_equivalent_with_mods(role: MenuItemRole) -> Option<(String, u64)> {
+    role.default_shortcut().and_then(shortcut_to_key_equivalent)
+}
+
+// AppKit has no chord key equivalents, so chords are shown after the label instead.
+fn command_title(cmd: &CommandItem) -> String {
+    let label = display_label(&cmd.label, cmd.badge.as_deref());
+    match cmd.shortcut {
+        Some(sequence) if sequence.is_chord() => {
+            format!("{}\t{}", label, sequence.label_for(Platform::MacOs))
+        }
+        _ => label,
+    }
+}
+
+fn menu_item_badge_class() -> Option<&'static AnyClass> {
+    // NSMenuItemBadge is macOS 14+.
+    AnyClass::get(c"NSMenuItemBadge")
+}
+
+/// The label without access key markers, which AppKit does not show, and
+/// with the badge appended where AppKit cannot show badges.
+fn display_label(label: &str, badge: Option<&str>) -> String {
+    let text = MnemonicLabel::parse(label).text;
+    match badge {
+        Some(badge) if menu_item_badge_class().is_none() => format!("{text} ({badge})"),
+        _ => text,
+    }
+}
+
+fn set_ns_item_badge(item: *mut AnyObject, badge: Option<&str>) {
+    let Some(class) = menu_item_badge_class() else {
+        return;
+    };
+    unsafe {
+        let badge = match badge {
+            Some(text) => {
+                let badge: *mut AnyObject = msg_send![class, alloc];
+                let badge: *mut AnyObject = msg_send![badge, initWithString: nsstring(text)];
+                badge
+            }
+            None => std::ptr::null_mut(),
+        };
+        let _: () = msg_send![item, setBadge: badge];
+    }
+}
+
+// Menu item images are drawn at their own size, so icons are scaled to fit.
+const MENU_ICON_SIZE: f32 = 16.0;
+
+fn set_ns_item_image(item: *mut AnyObject, icon: Option<&Icon>) {
+    let image = icon
+        .and_then(build_ns_image)
+        .unwrap_or(std::ptr::null_mut());
+    unsafe {
+        if !image.is_null() {
+            let _: () = msg_send![image, setSize: NSSize::new(MENU_ICON_SIZE, MENU_ICON_SIZE)];
+        }
+        let _: () = msg_send![item, setImage: image];
+    }
+}
+
+/// Decodes `icon` into an NSImage, or `None` if AppKit cannot read it.
+pub(crate) fn build_ns_image(icon: &Icon) -> Option<*mut AnyObject> {
+    unsafe {
+        match icon {
+            Icon::Png { bytes, is_template } => {
+                if bytes.is_empty() {
+                    return None;
+                }
+                let data: *mut AnyObject = msg_send![
+                    class!(NSData),
+                    dataWithBytes: bytes.as_ptr() as *const c_void,
+                    length: bytes.len()
+                ];
+                if data.is_null() {
+                    return None;
+                }
+                let image: *mut AnyObject = msg_send![class!(NSImage), alloc];
+                let image: *mut AnyObject = msg_send![image, initWithData: data];
+                if image.is_null() {
+                    return None;
+                }
+                if *is_template {
+                    let _: () = msg_send![image, setTemplate: true];
+                }
+                Some(image)
+            }
+        }
     }
 }
 
 fn shortcut_to_key_equivalent(shortcut: Shortcut) -> Option<(String, u64)> {
+    let mut mods = 0u64;
     let key = match shortcut.key {
         Key::Char(c) => {
             let ch = if c.is_ascii() {
@@ -679,17 +1128,20 @@ fn shortcut_to_key_equivalent(shortcut: Shortcut) -> Option<(String, u64)> {
         }
         Key::Enter => "\r".to_string(),
         Key::Escape => "\u{1b}".to_string(),
-        Key::F(n) => {
-            if (1..=12).contains(&n) {
-                let code = 0xF704u32 + (n as u32 - 1);
-                char::from_u32(code)?.to_string()
-            } else {
-                return None;
+        Key::Tab => "\t".to_string(),
+        Key::Space => " ".to_string(),
+        Key::Backspace => "\u{8}".to_string(),
+        Key::Numpad(key) => {
+            mods |= MOD_NUMERIC_PAD;
+            match key {
+                NumpadKey::Enter => "\u{3}".to_string(),
+                key => key.to_char()?.to_string(),
             }
         }
+        Key::Media(_) => return None,
+        key => char::from_u32(function_key_code(key)?)?.to_string(),
     };
 
-    let mut mods = 0u64;
     if shortcut.mods.shift {
         mods |= MOD_SHIFT;
     }
@@ -705,6 +1157,30 @@ fn shortcut_to_key_equivalent(shortcut: Shortcut) -> Option<(String, u64)> {
     Some((key, mods))
 }
 
+// NSEvent function-key unicodes (NSUpArrowFunctionKey and friends).
+fn function_key_code(key: Key) -> Option<u32> {
+    let code = match key {
+        Key::ArrowUp => 0xF700,
+        Key::ArrowDown => 0xF701,
+        Key::ArrowLeft => 0xF702,
+        Key::ArrowRight => 0xF703,
+        Key::F(n) if (1..=35).contains(&n) => 0xF704 + (n as u32 - 1),
+        Key::Insert => 0xF727,
+        Key::Delete => 0xF728,
+        Key::Home => 0xF729,
+        Key::End => 0xF72B,
+        Key::PageUp => 0xF72C,
+        Key::PageDown => 0xF72D,
+        Key::PrintScreen => 0xF72E,
+        Key::ScrollLock => 0xF72F,
+        Key::Pause => 0xF730,
+        Key::ContextMenu => 0xF735,
+        Key::Help => 0xF746,
+        _ => return None,
+    };
+    Some(code)
+}
+
 unsafe fn popup_menu(
     menu: *mut AnyObject,
     anchor: MenuAnchor,
@@ -761,6 +1237,12 @@ define_class!(
             self.invoke_from_sender(sender);
         }
 
+        // Only called for menus built with a state provider.
+        #[unsafe(method(validateMenuItem:))]
+        fn validate_menu_item(&self, item: &AnyObject) -> Bool {
+            validate_ns_menu_item(item, self.ivars().state.as_deref())
+        }
+
         #[unsafe(method(terminate:))]
         fn terminate(&self, sender: &AnyObject) {
             self.invoke_from_sender(sender);
@@ -847,12 +1329,23 @@ define_class!(
 );
 
 struct MenuTargetIvars {
-    on_command: Box<dyn Fn(CommandId) + 'static>,
+    events: Rc<MenuEvents>,
+    state: Option<Box<dyn CommandStateProvider>>,
+    app_menu: bool,
 }
 
 impl MenuTarget {
-    fn new(on_command: Box<dyn Fn(CommandId) + 'static>, mtm: MainThreadMarker) -> Retained<Self> {
-        let this = Self::alloc(mtm).set_ivars(MenuTargetIvars { on_command });
+    fn new(
+        events: Rc<MenuEvents>,
+        state: Option<Box<dyn CommandStateProvider>>,
+        app_menu: bool,
+        mtm: MainThreadMarker,
+    ) -> Retained<Self> {
+        let this = Self::alloc(mtm).set_ivars(MenuTargetIvars {
+            events,
+            state,
+            app_menu,
+        });
         unsafe { msg_send![super(this), init] }
     }
 
@@ -861,9 +1354,203 @@ impl MenuTarget {
         let Some(cmd) = tag_to_command_id(tag) else {
             return;
         };
-        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            (self.ivars().on_command)(cmd);
-        }));
+        if self.ivars().app_menu {
+            auto_check_app_menu(cmd);
+        }
+        self.ivars().events.emit(MenuEvent::CommandInvoked(cmd));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MenuKind {
+    /// A context or tray menu.
+    Popup,
+    /// Includes the top-level menus of the menu bar.
+    Submenu,
+}
+
+/// Gives `submenu` a delegate when it is lazy or its events are wanted.
+fn set_ns_menu_delegate(
+    item: *mut AnyObject,
+    submenu: *mut AnyObject,
+    kind: MenuKind,
+    provider: Option<&MenuProvider>,
+    ctx: &BuildContext,
+) {
+    if provider.is_none() && ctx.events.is_none() {
+        return;
+    }
+    let delegate = MenuDelegate::new(kind, provider.cloned(), ctx, main_thread_marker());
+    let delegate = Retained::as_ptr(&delegate) as *mut AnyObject;
+    unsafe {
+        let _: () = msg_send![submenu, setDelegate: delegate];
+        // The menu holds its delegate weakly; the item keeps it alive.
+        let _: () = msg_send![item, setRepresentedObject: delegate];
+    }
+}
+
+define_class!(
+    #[unsafe(super = NSObject)]
+    #[thread_kind = MainThreadOnly]
+    #[ivars = MenuDelegateIvars]
+    pub(crate) struct MenuDelegate;
+
+    impl MenuDelegate {
+        #[unsafe(method(menuNeedsUpdate:))]
+        fn menu_needs_update(&self, menu: &AnyObject) {
+            self.populate(menu as *const AnyObject as *mut AnyObject);
+        }
+
+        // Otherwise AppKit populates the menu to search it on every key event.
+        #[unsafe(method(menuHasKeyEquivalent:forEvent:target:action:))]
+        fn menu_has_key_equivalent(
+            &self,
+            _menu: &AnyObject,
+            _event: &AnyObject,
+            _target: *mut *mut AnyObject,
+            _action: *mut Sel,
+        ) -> Bool {
+            Bool::NO
+        }
+
+        #[unsafe(method(menuWillOpen:))]
+        fn menu_will_open(&self, menu: &AnyObject) {
+            if self.is_top_level(menu) {
+                self.emit(MenuEvent::Opened);
+            }
+        }
+
+        #[unsafe(method(menuDidClose:))]
+        fn menu_did_close(&self, menu: &AnyObject) {
+            if !self.is_top_level(menu) {
+                return;
+            }
+            self.emit(MenuEvent::Closed);
+            if self.ivars().kind == MenuKind::Popup {
+                // The chosen item's action is sent after the menu closes.
+                unsafe {
+                    let _: () = msg_send![
+                        self,
+                        performSelector: sel!(emitDismissedUnlessInvoked),
+                        withObject: std::ptr::null::<AnyObject>(),
+                        afterDelay: 0.0f64
+                    ];
+                }
+            }
+        }
+
+        #[unsafe(method(menu:willHighlightItem:))]
+        fn menu_will_highlight_item(&self, _menu: &AnyObject, item: *mut AnyObject) {
+            let cmd = if item.is_null() {
+                None
+            } else {
+                tag_to_command_id(unsafe { msg_send![item, tag] })
+            };
+            self.emit(MenuEvent::Highlighted(cmd));
+        }
+
+        #[unsafe(method(emitDismissedUnlessInvoked))]
+        fn emit_dismissed_unless_invoked(&self) {
+            let events = self.ivars().events.borrow().clone();
+            if let Some(events) = events {
+                if !events.invoked.get() {
+                    events.emit(MenuEvent::Dismissed);
+                }
+            }
+        }
+
+        // Only lazy menus take over updating and key equivalent lookup.
+        #[unsafe(method(respondsToSelector:))]
+        fn responds_to_selector(&self, selector: Sel) -> Bool {
+            if selector == sel!(menuNeedsUpdate:)
+                || selector == sel!(menuHasKeyEquivalent:forEvent:target:action:)
+            {
+                return Bool::new(self.ivars().provider.is_some());
+            }
+            unsafe { msg_send![super(self), respondsToSelector: selector] }
+        }
+    }
+);
+
+pub(crate) struct MenuDelegateIvars {
+    kind: MenuKind,
+    provider: Option<MenuProvider>,
+    target: Cell<*mut AnyObject>,
+    events: RefCell<Option<Rc<MenuEvents>>>,
+}
+
+impl MenuDelegate {
+    fn new(
+        kind: MenuKind,
+        provider: Option<MenuProvider>,
+        ctx: &BuildContext,
+        mtm: MainThreadMarker,
+    ) -> Retained<Self> {
+        let this = Self::alloc(mtm).set_ivars(MenuDelegateIvars {
+            kind,
+            provider,
+            target: Cell::new(ctx.target.unwrap_or(std::ptr::null_mut())),
+            events: RefCell::new(ctx.events.clone()),
+        });
+        unsafe { msg_send![super(this), init] }
+    }
+
+    /// The delegate of `menu`, if it is one of ours.
+    fn of<'a>(menu: *mut AnyObject) -> Option<&'a Self> {
+        unsafe {
+            let delegate: *mut AnyObject = msg_send![menu, delegate];
+            if delegate.is_null() {
+                return None;
+            }
+            let ours: Bool = msg_send![delegate, isKindOfClass: Self::class()];
+            ours.as_bool().then(|| &*(delegate as *const Self))
+        }
+    }
+
+    /// Whether `menu` is a context or tray menu or one of the menu bar's.
+    fn is_top_level(&self, menu: &AnyObject) -> bool {
+        if self.ivars().kind == MenuKind::Popup {
+            return true;
+        }
+        let supermenu: *mut AnyObject = unsafe { msg_send![menu, supermenu] };
+        !supermenu.is_null() && supermenu == current_main_menu()
+    }
+
+    fn retarget(&self, ctx: &BuildContext) {
+        self.ivars()
+            .target
+            .set(ctx.target.unwrap_or(std::ptr::null_mut()));
+        *self.ivars().events.borrow_mut() = ctx.events.clone();
+    }
+
+    fn emit(&self, event: MenuEvent) {
+        let events = self.ivars().events.borrow().clone();
+        if let Some(events) = events {
+            events.emit(event);
+        }
+    }
+
+    fn populate(&self, menu: *mut AnyObject) {
+        let Some(provider) = &self.ivars().provider else {
+            return;
+        };
+        let items = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| provider.items()))
+            .unwrap_or_default();
+        let target = self.ivars().target.get();
+        let validates: bool = unsafe { msg_send![menu, autoenablesItems] };
+        let ctx = BuildContext {
+            target: (!target.is_null()).then_some(target),
+            events: self.ivars().events.borrow().clone(),
+            validates,
+        };
+        unsafe {
+            let _: () = msg_send![menu, removeAllItems];
+            for item in &items {
+                if let Ok(Some(mi)) = build_ns_menu_item(item, &ctx) {
+                    let _: () = msg_send![menu, addItem: mi];
+                }
+            }
+        }
     }
 }
 
@@ -906,6 +1593,45 @@ unsafe impl RefEncode for NSPoint {
     const ENCODING_REF: Encoding = Encoding::Pointer(&Self::ENCODING);
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NSSize {
+    width: CGFloat,
+    height: CGFloat,
+}
+
+impl NSSize {
+    fn new(width: f32, height: f32) -> Self {
+        Self {
+            width: width as CGFloat,
+            height: height as CGFloat,
+        }
+    }
+}
+
+#[cfg(any(
+    not(target_vendor = "apple"),
+    all(target_os = "macos", target_pointer_width = "32")
+))]
+const NSSIZE_NAME: &str = "_NSSize";
+
+#[cfg(not(any(
+    not(target_vendor = "apple"),
+    all(target_os = "macos", target_pointer_width = "32")
+)))]
+const NSSIZE_NAME: &str = "CGSize";
+
+unsafe impl Encode for NSSize {
+    const ENCODING: Encoding = Encoding::Struct(
+        NSSIZE_NAME,
+        &[<CGFloat as Encode>::ENCODING, <CGFloat as Encode>::ENCODING],
+    );
+}
+
+unsafe impl RefEncode for NSSize {
+    const ENCODING_REF: Encoding = Encoding::Pointer(&Self::ENCODING);
+}
+
 /// Build an autoreleased NSString* from Rust &str.
 ///
 /// We use NSString::stringWithUTF8String: to avoid pulling more wrappers.
@@ -919,6 +1645,22 @@ fn nsstring(s: &str) -> *mut AnyObject {
     }
 }
 
+fn nsstring_to_string(ns: *mut AnyObject) -> Option<String> {
+    if ns.is_null() {
+        return None;
+    }
+    unsafe {
+        let cstr: *const std::os::raw::c_char = msg_send![ns, UTF8String];
+        if cstr.is_null() {
+            return None;
+        }
+        let text = std::ffi::CStr::from_ptr(cstr)
+            .to_string_lossy()
+            .into_owned();
+        Some(text)
+    }
+}
+
 #[cfg(target_pointer_width = "64")]
 type CGFloat = f64;
 
@@ -929,3 +1671,4 @@ const MOD_SHIFT: u64 = 1 << 17;
 const MOD_CONTROL: u64 = 1 << 18;
 const MOD_OPTION: u64 = 1 << 19;
 const MOD_COMMAND: u64 = 1 << 20;
+const MOD_NUMERIC_PAD: u64 = 1 << 21;
@@ -3,13 +3,20 @@
 #![allow(non_snake_case)]
 
 use core::ffi::c_void;
+use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::ptr::{null, null_mut};
+use std::rc::Rc;
 use std::sync::OnceLock;
 
 use makepad_shell_core::command::CommandId;
-use makepad_shell_core::shortcut::{Key, Shortcut};
-use makepad_shell_core::tray::{TrayCommandItem, TrayIcon, TrayMenuItem, TrayMenuModel, TrayModel};
+use makepad_shell_core::menu::{
+    CheckState, CommandItem, CommandStateProvider, Icon, MenuEvent, MenuItem, MenuPatchOp,
+    MnemonicLabel, apply_command_state, auto_check_menu_items, diff_menu_items, menu_item_at,
+    update_command_items, visible_menu_items,
+};
+use makepad_shell_core::platform::Platform;
+use makepad_shell_core::tray::{TrayIcon, TrayMenuModel, TrayModel};
 
 type BOOL = i32;
 type UINT = u32;
@@ -27,6 +34,8 @@ type HICON = *mut c_void;
 type HINSTANCE = *mut c_void;
 type HCURSOR = *mut c_void;
 type HBRUSH = *mut c_void;
+type HBITMAP = *mut c_void;
+type HDC = *mut c_void;
 type WNDPROC = Option<unsafe extern "system" fn(HWND, UINT, WPARAM, LPARAM) -> LRESULT>;
 
 #[repr(C)]
@@ -57,6 +66,45 @@ struct WNDCLASSW {
     lpszClassName: *const u16,
 }
 
+#[repr(C)]
+struct MENUITEMINFOW {
+    cbSize: UINT,
+    fMask: UINT,
+    fType: UINT,
+    fState: UINT,
+    wID: UINT,
+    hSubMenu: HMENU,
+    hbmpChecked: HBITMAP,
+    hbmpUnchecked: HBITMAP,
+    dwItemData: usize,
+    dwTypeData: *mut u16,
+    cch: UINT,
+    hbmpItem: HBITMAP,
+}
+
+#[repr(C)]
+struct MEASUREITEMSTRUCT {
+    CtlType: UINT,
+    CtlID: UINT,
+    itemID: UINT,
+    itemWidth: UINT,
+    itemHeight: UINT,
+    itemData: usize,
+}
+
+#[repr(C)]
+struct DRAWITEMSTRUCT {
+    CtlType: UINT,
+    CtlID: UINT,
+    itemID: UINT,
+    itemAction: UINT,
+    itemState: UINT,
+    hwndItem: HWND,
+    hDC: HDC,
+    rcItem: RECT,
+    itemData: usize,
+}
+
 #[repr(C)]
 struct CREATESTRUCTW {
     lpCreateParams: *mut c_void,
@@ -112,15 +160,34 @@ struct NOTIFYICONDATAW {
 const TRUE: BOOL = 1;
 const GWLP_USERDATA: i32 = -21;
 const MF_STRING: UINT = 0x0000;
+const MF_ENABLED: UINT = 0x0000;
+const MF_UNCHECKED: UINT = 0x0000;
 const MF_GRAYED: UINT = 0x0001;
 const MF_CHECKED: UINT = 0x0008;
 const MF_POPUP: UINT = 0x0010;
+const MF_BYPOSITION: UINT = 0x0400;
 const MF_SEPARATOR: UINT = 0x0800;
+const MIIM_STATE: UINT = 0x0001;
+const MIIM_ID: UINT = 0x0002;
+const MIIM_SUBMENU: UINT = 0x0004;
+const MIIM_CHECKMARKS: UINT = 0x0008;
+const MIIM_DATA: UINT = 0x0020;
+const MIIM_STRING: UINT = 0x0040;
+const MIIM_BITMAP: UINT = 0x0080;
+const MIIM_FTYPE: UINT = 0x0100;
+const MFT_RADIOCHECK: UINT = 0x0200;
+const MFS_HILITE: UINT = 0x0080;
+const HBMMENU_CALLBACK: HBITMAP = -1isize as HBITMAP;
+const ODT_MENU: UINT = 1;
+const DI_NORMAL: UINT = 0x0003;
 const TPM_RIGHTBUTTON: UINT = 0x0002;
 const TPM_RETURNCMD: UINT = 0x0100;
 const WM_NULL: UINT = 0x0000;
 const WM_CREATE: UINT = 0x0001;
+const WM_DRAWITEM: UINT = 0x002B;
+const WM_MEASUREITEM: UINT = 0x002C;
 const WM_CONTEXTMENU: UINT = 0x007B;
+const WM_MENUSELECT: UINT = 0x011F;
 const WM_LBUTTONDOWN: UINT = 0x0201;
 const WM_LBUTTONUP: UINT = 0x0202;
 const WM_LBUTTONDBLCLK: UINT = 0x0203;
@@ -161,7 +228,37 @@ unsafe extern "system" {
     fn SetWindowLongPtrW(hWnd: HWND, nIndex: i32, dwNewLong: LONG_PTR) -> LONG_PTR;
     fn GetWindowLongPtrW(hWnd: HWND, nIndex: i32) -> LONG_PTR;
     fn CreatePopupMenu() -> HMENU;
-    fn AppendMenuW(hMenu: HMENU, uFlags: UINT, uIDNewItem: usize, lpNewItem: *const u16) -> BOOL;
+    fn InsertMenuW(
+        hMenu: HMENU,
+        uPosition: UINT,
+        uFlags: UINT,
+        uIDNewItem: usize,
+        lpNewItem: *const u16,
+    ) -> BOOL;
+    fn InsertMenuItemW(
+        hMenu: HMENU,
+        item: UINT,
+        fByPosition: BOOL,
+        lpmi: *const MENUITEMINFOW,
+    ) -> BOOL;
+    fn GetMenuItemInfoW(
+        hMenu: HMENU,
+        item: UINT,
+        fByPosition: BOOL,
+        lpmii: *mut MENUITEMINFOW,
+    ) -> BOOL;
+    fn SetMenuItemInfoW(
+        hMenu: HMENU,
+        item: UINT,
+        fByPosition: BOOL,
+        lpmii: *const MENUITEMINFOW,
+    ) -> BOOL;
+    fn DeleteMenu(hMenu: HMENU, uPosition: UINT, uFlags: UINT) -> BOOL;
+    fn RemoveMenu(hMenu: HMENU, uPosition: UINT, uFlags: UINT) -> BOOL;
+    fn GetSubMenu(hMenu: HMENU, nPos: i32) -> HMENU;
+    fn GetMenuItemCount(hMenu: HMENU) -> i32;
+    fn EnableMenuItem(hMenu: HMENU, uIDEnableItem: UINT, uEnable: UINT) -> BOOL;
+    fn CheckMenuItem(hMenu: HMENU, uIDCheckItem: UINT, uCheck: UINT) -> DWORD;
     fn DestroyMenu(hMenu: HMENU) -> BOOL;
     fn TrackPopupMenu(
         hMenu: HMENU,
@@ -185,6 +282,17 @@ unsafe extern "system" {
         flags: UINT,
     ) -> HICON;
     fn LoadIconW(hInstance: HINSTANCE, lpIconName: *const u16) -> HICON;
+    fn DrawIconEx(
+        hdc: HDC,
+        xLeft: i32,
+        yTop: i32,
+        hIcon: HICON,
+        cxWidth: i32,
+        cyWidth: i32,
+        istepIfAniCur: UINT,
+        hbrFlickerFreeDraw: HBRUSH,
+        diFlags: UINT,
+    ) -> BOOL;
     fn DestroyIcon(hIcon: HICON) -> BOOL;
 }
 
@@ -208,6 +316,13 @@ const WM_TRAY_ICON: u32 = WM_APP + 0x240;
 static TRAY_WINDOW_CLASS_REGISTERED: OnceLock<bool> = OnceLock::new();
 static TRAY_WINDOW_CLASS_WIDE: OnceLock<Vec<u16>> = OnceLock::new();
 
+type MenuEventHandler = Rc<dyn Fn(MenuEvent) + 'static>;
+
+thread_local! {
+    // Set while TrackPopupMenu runs, for WM_MENUSELECT.
+    static TRACKED_MENU_EVENTS: RefCell<Option<MenuEventHandler>> = RefCell::new(None);
+}
+
 pub struct WindowsTrayHandle {
     runtime: *mut WindowsTrayRuntime,
     _not_send_or_sync: PhantomData<std::rc::Rc<()>>,
@@ -216,22 +331,33 @@ pub struct WindowsTrayHandle {
 struct WindowsTrayRuntime {
     hwnd: HWND,
     menu: HMENU,
+    /// The whole model. Win32 menus have no hidden or alternate items, so
+    /// the native menu holds only `visible_menu_items` of these.
+    items: Vec<MenuItem>,
+    menu_icons: MenuIcons,
     icon: HICON,
-    on_command: Box<dyn Fn(CommandId) + 'static>,
+    on_event: MenuEventHandler,
+    state: Option<Box<dyn CommandStateProvider + 'static>>,
     on_activate: Box<dyn Fn() + 'static>,
 }
 
 impl WindowsTrayHandle {
     pub fn update_menu(&mut self, menu: &TrayMenuModel) -> Result<(), WindowsTrayError> {
-        let new_menu = build_hmenu(&menu.items)?;
         let runtime = self.runtime_mut()?;
-        if !runtime.menu.is_null() {
-            unsafe {
-                let _ = DestroyMenu(runtime.menu);
-            }
-        }
-        runtime.menu = new_menu;
-        Ok(())
+        runtime.update_items(|items| *items = menu.items.clone())
+    }
+
+    /// Runs `update` on the items of command `id` and changes only those
+    /// native items, or rebuilds the menu if a change does not fit.
+    pub fn update_command(
+        &mut self,
+        id: CommandId,
+        update: impl FnMut(&mut CommandItem),
+    ) -> Result<(), WindowsTrayError> {
+        let runtime = self.runtime_mut()?;
+        runtime.update_items(|items| {
+            update_command_items(items, id, update);
+        })
     }
 
     pub fn update_icon(&mut self, icon: &TrayIcon) -> Result<(), WindowsTrayError> {
@@ -272,6 +398,38 @@ impl Drop for WindowsTrayHandle {
 }
 
 impl WindowsTrayRuntime {
+    /// Runs `update` on the model and patches the native menu with the
+    /// difference in shown items, so showing or hiding an item inserts or
+    /// removes it. Rebuilds the menu if the patch does not fit.
+    fn update_items(
+        &mut self,
+        update: impl FnOnce(&mut Vec<MenuItem>),
+    ) -> Result<(), WindowsTrayError> {
+        let shown = visible_menu_items(&self.items);
+        update(&mut self.items);
+        if self.menu.is_null() {
+            return self.rebuild_menu();
+        }
+        let new_shown = visible_menu_items(&self.items);
+        let ops = diff_menu_items(&shown, &new_shown);
+        match apply_hmenu_patch(self.menu, &ops, &new_shown, &mut self.menu_icons) {
+            Ok(true) => Ok(()),
+            _ => self.rebuild_menu(),
+        }
+    }
+
+    /// Replaces the native menu with one built from the shown `items`.
+    fn rebuild_menu(&mut self) -> Result<(), WindowsTrayError> {
+        let new_menu = build_hmenu(&visible_menu_items(&self.items), &mut self.menu_icons)?;
+        if !self.menu.is_null() {
+            unsafe {
+                let _ = DestroyMenu(self.menu);
+            }
+        }
+        self.menu = new_menu;
+        Ok(())
+    }
+
     fn add_tray_icon(&self, tooltip: Option<&str>) -> Result<(), WindowsTrayError> {
         let mut data = base_notify_data(self.hwnd);
         data.uFlags = NIF_MESSAGE | NIF_ICON;
@@ -343,9 +501,10 @@ impl WindowsTrayRuntime {
                 self.icon = null_mut();
             }
         }
+        self.menu_icons.destroy();
     }
 
-    fn handle_tray_message(&self, event: u32) {
+    fn handle_tray_message(&mut self, event: u32) {
         match event {
             WM_LBUTTONDOWN | WM_LBUTTONUP | WM_LBUTTONDBLCLK => {
                 (self.on_activate)();
@@ -357,7 +516,7 @@ impl WindowsTrayRuntime {
         }
     }
 
-    fn show_menu(&self) {
+    fn show_menu(&mut self) {
         unsafe {
             if self.menu.is_null() || self.hwnd.is_null() {
                 return;
@@ -368,7 +527,23 @@ impl WindowsTrayRuntime {
                 return;
             }
 
+            // Win32 has no per-submenu open notification here; update them all now.
+            if let Some(state) = self.state.take() {
+                let _ = self.update_items(|items| {
+                    apply_command_state(items, state.as_ref());
+                });
+                self.state = Some(state);
+            }
+            let _ = populate_lazy_hmenus(
+                self.menu,
+                &visible_menu_items(&self.items),
+                self.state.as_deref(),
+                &mut self.menu_icons,
+            );
+
             let _ = SetForegroundWindow(self.hwnd);
+            (self.on_event)(MenuEvent::Opened);
+            TRACKED_MENU_EVENTS.with(|slot| *slot.borrow_mut() = Some(self.on_event.clone()));
             let selected = TrackPopupMenu(
                 self.menu,
                 TPM_RETURNCMD | TPM_RIGHTBUTTON,
@@ -378,11 +553,17 @@ impl WindowsTrayRuntime {
                 self.hwnd,
                 null(),
             ) as u32;
-
-            if selected != 0 {
-                if let Some(command) = CommandId::new(selected as u64) {
-                    (self.on_command)(command);
+            TRACKED_MENU_EVENTS.with(|slot| *slot.borrow_mut() = None);
+            (self.on_event)(MenuEvent::Closed);
+
+            match CommandId::new(selected as u64) {
+                Some(command) => {
+                    let _ = self.update_items(|items| {
+                        auto_check_menu_items(items, command);
+                    });
+                    (self.on_event)(MenuEvent::CommandInvoked(command));
                 }
+                None => (self.on_event)(MenuEvent::Dismissed),
             }
 
             let _ = PostMessageW(self.hwnd, WM_NULL, 0, 0);
@@ -394,17 +575,36 @@ pub fn create_tray_windows(
     model: TrayModel,
     on_command: Box<dyn Fn(CommandId) + 'static>,
     on_activate: Box<dyn Fn() + 'static>,
+) -> Result<WindowsTrayHandle, WindowsTrayError> {
+    let on_event = move |event: MenuEvent| {
+        if let Some(command) = event.command() {
+            on_command(command);
+        }
+    };
+    create_tray_windows_with_events(model, Box::new(on_event), None, on_activate)
+}
+
+pub fn create_tray_windows_with_events(
+    model: TrayModel,
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+    state: Option<Box<dyn CommandStateProvider + 'static>>,
+    on_activate: Box<dyn Fn() + 'static>,
 ) -> Result<WindowsTrayHandle, WindowsTrayError> {
     ensure_window_class_registered()?;
 
-    let menu = build_hmenu(&model.menu.items)?;
+    let items = model.menu.items;
+    let mut menu_icons = MenuIcons::default();
+    let menu = build_hmenu(&visible_menu_items(&items), &mut menu_icons)?;
     let icon = build_hicon(&model.icon)?;
 
     let runtime = Box::new(WindowsTrayRuntime {
         hwnd: null_mut(),
         menu,
+        items,
+        menu_icons,
         icon,
-        on_command,
+        on_event: Rc::from(on_event),
+        state,
         on_activate,
     });
     let runtime_ptr = Box::into_raw(runtime);
@@ -475,6 +675,58 @@ unsafe extern "system" fn tray_window_proc(
         return 0;
     }
 
+    // Item icons are drawn by the owner window; the HICON is the item data.
+    if msg == WM_MEASUREITEM {
+        let measure = lparam as *mut MEASUREITEMSTRUCT;
+        if !measure.is_null() && unsafe { (*measure).CtlType } == ODT_MENU {
+            unsafe {
+                (*measure).itemWidth = MENU_ICON_SIZE as UINT;
+                (*measure).itemHeight = MENU_ICON_SIZE as UINT;
+            }
+            return TRUE as LRESULT;
+        }
+    }
+    if msg == WM_DRAWITEM {
+        let draw = lparam as *const DRAWITEMSTRUCT;
+        if !draw.is_null() && unsafe { (*draw).CtlType } == ODT_MENU {
+            unsafe {
+                let draw = &*draw;
+                let height = draw.rcItem.bottom - draw.rcItem.top;
+                let top = draw.rcItem.top + (height - MENU_ICON_SIZE) / 2;
+                let _ = DrawIconEx(
+                    draw.hDC,
+                    draw.rcItem.left,
+                    top,
+                    draw.itemData as HICON,
+                    MENU_ICON_SIZE,
+                    MENU_ICON_SIZE,
+                    0,
+                    null_mut(),
+                    DI_NORMAL,
+                );
+            }
+            return TRUE as LRESULT;
+        }
+    }
+
+    if msg == WM_MENUSELECT {
+        let flags = (wparam >> 16) as UINT & 0xFFFF;
+        let menu = lparam as HMENU;
+        // 0xFFFF without a menu means the menu closed; show_menu reports that.
+        if flags != 0xFFFF || !menu.is_null() {
+            let command = if flags & (MF_POPUP | MF_SEPARATOR) != 0 {
+                None
+            } else {
+                menu_command_at(menu, (wparam & 0xFFFF) as UINT)
+            };
+            let on_event = TRACKED_MENU_EVENTS.with(|slot| slot.borrow().clone());
+            if let Some(on_event) = on_event {
+                on_event(MenuEvent::Highlighted(command));
+            }
+        }
+        return 0;
+    }
+
     let runtime_ptr = unsafe { GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowsTrayRuntime };
     if runtime_ptr.is_null() {
         return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
@@ -518,14 +770,14 @@ fn ensure_window_class_registered() -> Result<(), WindowsTrayError> {
     }
 }
 
-fn build_hmenu(items: &[TrayMenuItem]) -> Result<HMENU, WindowsTrayError> {
+fn build_hmenu(items: &[MenuItem], icons: &mut MenuIcons) -> Result<HMENU, WindowsTrayError> {
     let menu = unsafe { CreatePopupMenu() };
     if menu.is_null() {
         return Err(WindowsTrayError::Unsupported);
     }
 
-    for item in items {
-        if let Err(err) = append_menu_item(menu, item) {
+    for (index, item) in items.iter().enumerate() {
+        if let Err(err) = insert_menu_item(menu, index as UINT, item, icons) {
             unsafe {
                 let _ = DestroyMenu(menu);
             }
@@ -536,49 +788,315 @@ fn build_hmenu(items: &[TrayMenuItem]) -> Result<HMENU, WindowsTrayError> {
     Ok(menu)
 }
 
-fn append_menu_item(menu: HMENU, item: &TrayMenuItem) -> Result<(), WindowsTrayError> {
+/// Replaces the contents of every lazy submenu with its provider's items.
+fn populate_lazy_hmenus(
+    menu: HMENU,
+    items: &[MenuItem],
+    state: Option<&dyn CommandStateProvider>,
+    icons: &mut MenuIcons,
+) -> Result<(), WindowsTrayError> {
+    for (index, item) in items.iter().enumerate() {
+        let MenuItem::Submenu(submenu) = item else {
+            continue;
+        };
+        let hsubmenu = unsafe { GetSubMenu(menu, index as i32) };
+        if hsubmenu.is_null() {
+            continue;
+        }
+        let Some(provider) = &submenu.provider else {
+            populate_lazy_hmenus(hsubmenu, &submenu.items, state, icons)?;
+            continue;
+        };
+        let mut provided = visible_menu_items(&provider.items());
+        if let Some(state) = state {
+            apply_command_state(&mut provided, state);
+        }
+        unsafe {
+            while GetMenuItemCount(hsubmenu) > 0 {
+                if DeleteMenu(hsubmenu, 0, MF_BYPOSITION) == 0 {
+                    return Err(WindowsTrayError::Unsupported);
+                }
+            }
+        }
+        for (position, item) in provided.iter().enumerate() {
+            insert_menu_item(hsubmenu, position as UINT, item, icons)?;
+        }
+        populate_lazy_hmenus(hsubmenu, &provided, state, icons)?;
+    }
+    Ok(())
+}
+
+fn insert_menu_item(
+    menu: HMENU,
+    position: UINT,
+    item: &MenuItem,
+    icons: &mut MenuIcons,
+) -> Result<(), WindowsTrayError> {
     match item {
-        TrayMenuItem::Separator => unsafe {
-            if AppendMenuW(menu, MF_SEPARATOR, 0, null()) == 0 {
+        MenuItem::Separator(_) => unsafe {
+            if InsertMenuW(menu, position, MF_BYPOSITION | MF_SEPARATOR, 0, null()) == 0 {
                 return Err(WindowsTrayError::Unsupported);
             }
         },
-        TrayMenuItem::Command(command) => {
+        MenuItem::Command(command) => {
             let menu_id = command_id_to_menu_id(command.id)? as usize;
-            let mut flags = MF_STRING;
+            let mut flags = MF_BYPOSITION | MF_STRING;
             if !command.enabled {
                 flags |= MF_GRAYED;
             }
-            if command.checked {
+            // There is no mixed mark; Mixed shows as checked.
+            if command.checked != CheckState::Off {
                 flags |= MF_CHECKED;
             }
 
             let label = menu_label(command);
             let wide = wide_null(&label);
             unsafe {
-                if AppendMenuW(menu, flags, menu_id, wide.as_ptr()) == 0 {
+                if InsertMenuW(menu, position, flags, menu_id, wide.as_ptr()) == 0 {
                     return Err(WindowsTrayError::Unsupported);
                 }
             }
+            if command.radio_group.is_some() {
+                // InsertMenuW has no radio flag; 0x200 there means MF_USECHECKBITMAPS.
+                let mut info = menu_item_info(MIIM_FTYPE);
+                info.fType = MF_STRING | MFT_RADIOCHECK;
+                unsafe {
+                    let _ = SetMenuItemInfoW(menu, position, TRUE, &info);
+                }
+            }
+            set_menu_item_icon(menu, position, command.icon.as_ref(), icons);
         }
-        TrayMenuItem::Submenu(submenu) => {
-            let nested = build_hmenu(&submenu.items)?;
-            let wide = wide_null(&submenu.label);
+        MenuItem::Submenu(submenu) => {
+            let nested = build_hmenu(&submenu.items, icons)?;
+            let wide = wide_null(&item_label(&submenu.label, submenu.badge.as_deref()));
+            let flags = MF_BYPOSITION | MF_POPUP | MF_STRING;
             unsafe {
-                if AppendMenuW(menu, MF_POPUP | MF_STRING, nested as usize, wide.as_ptr()) == 0 {
+                if InsertMenuW(menu, position, flags, nested as usize, wide.as_ptr()) == 0 {
                     let _ = DestroyMenu(nested);
                     return Err(WindowsTrayError::Unsupported);
                 }
             }
+            set_menu_item_icon(menu, position, submenu.icon.as_ref(), icons);
         }
     }
     Ok(())
 }
 
-fn menu_label(item: &TrayCommandItem) -> String {
-    let mut label = item.label.clone();
-    if let Some(shortcut) = item.shortcut {
-        let text = shortcut_label(shortcut);
+/// Shows `icon` in the item's bitmap slot, or clears it. Icons that do not
+/// load leave the item text only.
+fn set_menu_item_icon(
+    menu: HMENU,
+    position: UINT,
+    icon: Option<&Icon>,
+    icons: &mut MenuIcons,
+) -> bool {
+    let hicon = icon.map_or(null_mut(), |icon| icons.get(icon));
+    let mut info = menu_item_info(MIIM_BITMAP | MIIM_DATA);
+    if !hicon.is_null() {
+        info.hbmpItem = HBMMENU_CALLBACK;
+        info.dwItemData = hicon as usize;
+    }
+    unsafe { SetMenuItemInfoW(menu, position, TRUE, &info) != 0 }
+}
+
+const MENU_ICON_SIZE: i32 = 16;
+
+/// Item icons loaded so far, shared by equal `Icon`s and destroyed with the
+/// tray.
+#[derive(Default)]
+struct MenuIcons {
+    icons: Vec<(Icon, HICON)>,
+}
+
+impl MenuIcons {
+    fn get(&mut self, icon: &Icon) -> HICON {
+        if let Some((_, hicon)) = self.icons.iter().find(|(known, _)| known == icon) {
+            return *hicon;
+        }
+        let Icon::Png { bytes, .. } = icon;
+        if bytes.is_empty() {
+            return null_mut();
+        }
+        let hicon = unsafe {
+            CreateIconFromResourceEx(
+                bytes.as_ptr() as *mut u8,
+                bytes.len() as DWORD,
+                TRUE,
+                0x0003_0000,
+                MENU_ICON_SIZE,
+                MENU_ICON_SIZE,
+                0,
+            )
+        };
+        if !hicon.is_null() {
+            self.icons.push((icon.clone(), hicon));
+        }
+        hicon
+    }
+
+    fn destroy(&mut self) {
+        for (_, hicon) in self.icons.drain(..) {
+            unsafe {
+                let _ = DestroyIcon(hicon);
+            }
+        }
+    }
+}
+
+/// Applies a `diff_menu_items` patch to `menu`. `new_items` is the model the
+/// patch leads to. Returns `Ok(false)` when the menu no longer matches the
+/// model the patch was computed from; the caller then rebuilds it.
+fn apply_hmenu_patch(
+    menu: HMENU,
+    ops: &[MenuPatchOp],
+    new_items: &[MenuItem],
+    icons: &mut MenuIcons,
+) -> Result<bool, WindowsTrayError> {
+    for op in ops {
+        let applied = match op {
+            MenuPatchOp::Remove { parent, index } => {
+                let parent = hmenu_at(menu, parent);
+                // DeleteMenu also destroys a removed submenu.
+                !parent.is_null()
+                    && unsafe { DeleteMenu(parent, *index as UINT, MF_BYPOSITION) } != 0
+            }
+            MenuPatchOp::Insert {
+                parent,
+                index,
+                item,
+            } => {
+                let parent = hmenu_at(menu, parent);
+                if parent.is_null() {
+                    return Ok(false);
+                }
+                insert_menu_item(parent, *index as UINT, item, icons)?;
+                true
+            }
+            MenuPatchOp::Move { parent, from, to } => {
+                let parent = hmenu_at(menu, parent);
+                !parent.is_null() && move_menu_item(parent, *from as UINT, *to as UINT)
+            }
+            MenuPatchOp::SetLabel { path, .. }
+            | MenuPatchOp::SetShortcut { path, .. }
+            | MenuPatchOp::SetBadge { path, .. } => {
+                let label = match menu_item_at(new_items, path) {
+                    Some(MenuItem::Command(command)) => menu_label(command),
+                    Some(MenuItem::Submenu(submenu)) => {
+                        item_label(&submenu.label, submenu.badge.as_deref())
+                    }
+                    _ => return Ok(false),
+                };
+                let (parent, index) = hmenu_parent_of(menu, path);
+                let mut wide = wide_null(&label);
+                let mut info = menu_item_info(MIIM_STRING);
+                info.dwTypeData = wide.as_mut_ptr();
+                !parent.is_null() && unsafe { SetMenuItemInfoW(parent, index, TRUE, &info) } != 0
+            }
+            MenuPatchOp::SetIcon { path, icon } => {
+                let (parent, index) = hmenu_parent_of(menu, path);
+                !parent.is_null() && set_menu_item_icon(parent, index, icon.as_ref(), icons)
+            }
+            MenuPatchOp::SetCommandId { path, id } => {
+                let (parent, index) = hmenu_parent_of(menu, path);
+                let mut info = menu_item_info(MIIM_ID);
+                info.wID = command_id_to_menu_id(*id)?;
+                !parent.is_null() && unsafe { SetMenuItemInfoW(parent, index, TRUE, &info) } != 0
+            }
+            MenuPatchOp::SetEnabled { path, enabled } => {
+                let (parent, index) = hmenu_parent_of(menu, path);
+                let flag = if *enabled { MF_ENABLED } else { MF_GRAYED };
+                // Returns the previous state, or -1 if the item does not exist.
+                !parent.is_null()
+                    && unsafe { EnableMenuItem(parent, index, MF_BYPOSITION | flag) } != -1
+            }
+            // Patches are diffs of the shown items, which are all visible.
+            MenuPatchOp::SetVisible { .. } => false,
+            // Lazy submenus are filled in when the menu pops up.
+            MenuPatchOp::SetProvider { .. } => true,
+            MenuPatchOp::SetChecked { path, checked } => {
+                let (parent, index) = hmenu_parent_of(menu, path);
+                let flag = match checked {
+                    CheckState::Off => MF_UNCHECKED,
+                    CheckState::On | CheckState::Mixed => MF_CHECKED,
+                };
+                !parent.is_null()
+                    && unsafe { CheckMenuItem(parent, index, MF_BYPOSITION | flag) } != DWORD::MAX
+            }
+        };
+        if !applied {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The submenu at `path` below `menu`, or null.
+fn hmenu_at(menu: HMENU, path: &[usize]) -> HMENU {
+    let mut menu = menu;
+    for &index in path {
+        if menu.is_null() {
+            break;
+        }
+        menu = unsafe { GetSubMenu(menu, index as i32) };
+    }
+    menu
+}
+
+fn hmenu_parent_of(menu: HMENU, path: &[usize]) -> (HMENU, UINT) {
+    match path.split_last() {
+        Some((&index, parent)) => (hmenu_at(menu, parent), index as UINT),
+        None => (null_mut(), 0),
+    }
+}
+
+fn menu_item_info(mask: UINT) -> MENUITEMINFOW {
+    MENUITEMINFOW {
+        cbSize: std::mem::size_of::<MENUITEMINFOW>() as UINT,
+        fMask: mask,
+        fType: 0,
+        fState: 0,
+        wID: 0,
+        hSubMenu: null_mut(),
+        hbmpChecked: null_mut(),
+        hbmpUnchecked: null_mut(),
+        dwItemData: 0,
+        dwTypeData: null_mut(),
+        cch: 0,
+        hbmpItem: null_mut(),
+    }
+}
+
+// Win32 has no move; the item is copied out, removed without destroying its
+// submenu, and inserted again.
+fn move_menu_item(menu: HMENU, from: UINT, to: UINT) -> bool {
+    let mask = MIIM_FTYPE
+        | MIIM_STATE
+        | MIIM_ID
+        | MIIM_SUBMENU
+        | MIIM_CHECKMARKS
+        | MIIM_DATA
+        | MIIM_STRING
+        | MIIM_BITMAP;
+    unsafe {
+        let mut info = menu_item_info(MIIM_STRING);
+        if GetMenuItemInfoW(menu, from, TRUE, &mut info) == 0 {
+            return false;
+        }
+        let mut text = vec![0u16; info.cch as usize + 1];
+        let mut info = menu_item_info(mask);
+        info.dwTypeData = text.as_mut_ptr();
+        info.cch = text.len() as UINT;
+        if GetMenuItemInfoW(menu, from, TRUE, &mut info) == 0 {
+            return false;
+        }
+        RemoveMenu(menu, from, MF_BYPOSITION) != 0 && InsertMenuItemW(menu, to, TRUE, &info) != 0
+    }
+}
+
+fn menu_label(item: &CommandItem) -> String {
+    let mut label = item_label(&item.label, item.badge.as_deref());
+    if let Some(sequence) = item.shortcut {
+        let text = sequence.label_for(Platform::Windows);
         if !text.is_empty() {
             label.push('\t');
             label.push_str(&text);
@@ -587,30 +1105,43 @@ fn menu_label(item: &TrayCommandItem) -> String {
     label
 }
 
-fn shortcut_label(shortcut: Shortcut) -> String {
-    let mut parts: Vec<String> = Vec::new();
-    if shortcut.mods.ctrl {
-        parts.push("Ctrl".to_string());
+// Win32 menus have no badges; the badge follows the label, with any `&`
+// escaped so it does not become an access key.
+fn item_label(label: &str, badge: Option<&str>) -> String {
+    let text = MnemonicLabel::parse(label).text_for(Platform::Windows);
+    match badge {
+        Some(badge) => format!("{text} ({})", badge.replace('&', "&&")),
+        None => text,
     }
-    if shortcut.mods.alt {
-        parts.push("Alt".to_string());
+}
+
+/// The command of the highlighted item in `menu`. WM_MENUSELECT only
+/// carries the low 16 bits of its id, which ids from different registry
+/// namespaces or names can share, so the item is found by its highlight.
+fn menu_command_at(menu: HMENU, low_id: UINT) -> Option<CommandId> {
+    if menu.is_null() {
+        return None;
     }
-    if shortcut.mods.shift {
-        parts.push("Shift".to_string());
+    let count = unsafe { GetMenuItemCount(menu) };
+    let mut candidates = Vec::new();
+    for position in 0..count.max(0) as UINT {
+        let mut info = menu_item_info(MIIM_ID | MIIM_SUBMENU | MIIM_STATE);
+        if unsafe { GetMenuItemInfoW(menu, position, TRUE, &mut info) } == 0 {
+            continue;
+        }
+        if !info.hSubMenu.is_null() || info.wID & 0xFFFF != low_id {
+            continue;
+        }
+        if info.fState & MFS_HILITE != 0 {
+            return CommandId::new(info.wID as u64);
+        }
+        candidates.push(info.wID);
     }
-    if shortcut.mods.meta {
-        parts.push("Win".to_string());
+    // Without a highlighted match, the low bits only count if unambiguous.
+    match candidates[..] {
+        [id] => CommandId::new(id as u64),
+        _ => None,
     }
-
-    let key = match shortcut.key {
-        Key::Char(ch) => ch.to_ascii_uppercase().to_string(),
-        Key::Enter => "Enter".to_string(),
-        Key::Escape => "Esc".to_string(),
-        Key::F(n) => format!("F{n}"),
-    };
-
-    parts.push(key);
-    parts.join("+")
 }
 
 fn command_id_to_menu_id(id: CommandId) -> Result<u32, WindowsTrayError> {
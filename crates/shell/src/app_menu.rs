@@ -1,18 +1,121 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
 use makepad_shell_core::command::CommandId;
-use makepad_shell_core::menu::MenuBarModel;
+use makepad_shell_core::menu::{
+    CheckState, CommandItem, CommandStateProvider, MenuBarModel, MenuEvent, auto_check_menu_items,
+    update_command_items,
+};
+use makepad_shell_core::shortcut::ShortcutSequence;
+
+use crate::locale::LocalizedState;
+use crate::{Localization, ShellError};
 
-use crate::ShellError;
+thread_local! {
+    /// The app menu as set, before localization.
+    static APP_MENU: RefCell<Option<MenuBarModel>> = const { RefCell::new(None) };
+}
 
 pub struct AppMenu;
 
+/// Changes single items of the app menu in place, keeping the model the
+/// menu was set from in sync. Applies to whichever menu bar was set last.
+pub struct AppMenuHandle {
+    _not_send_or_sync: PhantomData<Rc<()>>,
+}
+
+impl AppMenuHandle {
+    /// Replaces the model but keeps the callbacks; unchanged items stay as
+    /// they are.
+    pub fn update_menu(&mut self, menu: MenuBarModel) -> Result<(), ShellError> {
+        update_platform_app_menu(localize_menu_bar(&menu))?;
+        APP_MENU.with(|slot| *slot.borrow_mut() = Some(menu));
+        Ok(())
+    }
+
+    pub fn set_enabled(&mut self, id: CommandId, enabled: bool) -> Result<(), ShellError> {
+        self.update_command(id, |cmd| cmd.enabled = enabled)
+    }
+
+    pub fn set_checked(
+        &mut self,
+        id: CommandId,
+        checked: impl Into<CheckState>,
+    ) -> Result<(), ShellError> {
+        let checked = checked.into();
+        self.update_command(id, |cmd| cmd.checked = checked)
+    }
+
+    pub fn set_label(&mut self, id: CommandId, label: impl Into<String>) -> Result<(), ShellError> {
+        let label = label.into();
+        self.update_command(id, |cmd| cmd.label = label.clone())
+    }
+
+    pub fn set_shortcut(
+        &mut self,
+        id: CommandId,
+        shortcut: Option<ShortcutSequence>,
+    ) -> Result<(), ShellError> {
+        self.update_command(id, |cmd| cmd.shortcut = shortcut)
+    }
+
+    /// Runs `update` on every item of command `id` and changes only those
+    /// native items. A change the native menu cannot make in place, e.g. a
+    /// new role or radio group, rebuilds it.
+    pub fn update_command(
+        &mut self,
+        id: CommandId,
+        mut update: impl FnMut(&mut CommandItem),
+    ) -> Result<(), ShellError> {
+        // The native items show resolved labels and badges; resolve only new ones.
+        update_platform_app_menu_command(id, |cmd| {
+            let (label, badge) = (cmd.label.clone(), cmd.badge.clone());
+            update(cmd);
+            if cmd.label != label {
+                cmd.label = Localization::resolve(&cmd.label);
+            }
+            if cmd.badge != badge {
+                cmd.badge = cmd.badge.as_deref().map(Localization::resolve);
+            }
+        })?;
+        APP_MENU.with(|slot| {
+            if let Some(menu) = slot.borrow_mut().as_mut() {
+                for top in &mut menu.menus {
+                    update_command_items(&mut top.items, id, &mut update);
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
 impl AppMenu {
     pub fn set(
         menu: MenuBarModel,
         on_command: impl Fn(CommandId) + 'static,
-    ) -> Result<(), ShellError> {
+    ) -> Result<AppMenuHandle, ShellError> {
         set_app_menu(menu, on_command)
     }
 
+    /// Like `set`, but reports menus opening, closing and highlighting too.
+    pub fn set_with_events(
+        menu: MenuBarModel,
+        on_event: impl Fn(MenuEvent) + 'static,
+    ) -> Result<AppMenuHandle, ShellError> {
+        set_app_menu_with_events(menu, on_event)
+    }
+
+    /// Like `set_with_events`, but asks `state` for each command's label,
+    /// enabled and checked state before its menu shows.
+    pub fn set_with_state(
+        menu: MenuBarModel,
+        on_event: impl Fn(MenuEvent) + 'static,
+        state: impl CommandStateProvider + 'static,
+    ) -> Result<AppMenuHandle, ShellError> {
+        set_app_menu_with_state(menu, on_event, state)
+    }
+
     pub fn clear() -> Result<(), ShellError> {
         clear_app_menu()
     }
@@ -21,21 +124,81 @@ impl AppMenu {
 pub fn set_app_menu(
     menu: MenuBarModel,
     on_command: impl Fn(CommandId) + 'static,
-) -> Result<(), ShellError> {
+) -> Result<AppMenuHandle, ShellError> {
+    set_app_menu_with_events(menu, move |event| {
+        if let Some(cmd) = event.command() {
+            on_command(cmd);
+        }
+    })
+}
+
+pub fn set_app_menu_with_events(
+    menu: MenuBarModel,
+    on_event: impl Fn(MenuEvent) + 'static,
+) -> Result<AppMenuHandle, ShellError> {
+    set_app_menu_inner(menu, Box::new(on_event), None)
+}
+
+pub fn set_app_menu_with_state(
+    menu: MenuBarModel,
+    on_event: impl Fn(MenuEvent) + 'static,
+    state: impl CommandStateProvider + 'static,
+) -> Result<AppMenuHandle, ShellError> {
+    set_app_menu_inner(
+        menu,
+        Box::new(on_event),
+        Some(Box::new(LocalizedState(state))),
+    )
+}
+
+fn set_app_menu_inner(
+    menu: MenuBarModel,
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+    state: Option<Box<dyn CommandStateProvider + 'static>>,
+) -> Result<AppMenuHandle, ShellError> {
+    // The backend auto-checks its own copy of the model; doing the same to
+    // the source keeps a later relocalize from undoing it.
+    let on_event = Box::new(move |event: MenuEvent| {
+        if let MenuEvent::CommandInvoked(id) = event {
+            APP_MENU.with(|slot| {
+                if let Ok(mut slot) = slot.try_borrow_mut()
+                    && let Some(menu) = slot.as_mut()
+                {
+                    for top in &mut menu.menus {
+                        auto_check_menu_items(&mut top.items, id);
+                    }
+                }
+            });
+        }
+        on_event(event);
+    });
+    let handle = set_platform_app_menu(localize_menu_bar(&menu), on_event, state)?;
+    APP_MENU.with(|slot| *slot.borrow_mut() = Some(menu));
+    Ok(handle)
+}
+
+fn set_platform_app_menu(
+    menu: MenuBarModel,
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+    state: Option<Box<dyn CommandStateProvider + 'static>>,
+) -> Result<AppMenuHandle, ShellError> {
     #[cfg(target_os = "macos")]
     {
         #[cfg(feature = "platforms")]
         {
-            return makepad_shell_platforms::menu::macos::set_app_menu_macos(
-                menu,
-                Box::new(on_command),
+            makepad_shell_platforms::menu::macos::set_app_menu_macos_with_events(
+                menu, on_event, state,
             )
-            .map_err(|_| ShellError::Unsupported);
+            .map_err(|_| ShellError::Unsupported)?;
+            return Ok(AppMenuHandle {
+                _not_send_or_sync: PhantomData,
+            });
         }
         #[cfg(not(feature = "platforms"))]
         {
             let _ = menu;
-            let _ = on_command;
+            let _ = on_event;
+            let _ = state;
             return Err(ShellError::Unsupported);
         }
     }
@@ -43,11 +206,70 @@ pub fn set_app_menu(
     #[cfg(not(target_os = "macos"))]
     {
         let _ = menu;
-        let _ = on_command;
+        let _ = on_event;
+        let _ = state;
+        Err(ShellError::Unsupported)
+    }
+}
+
+fn update_platform_app_menu(menu: MenuBarModel) -> Result<(), ShellError> {
+    #[cfg(all(target_os = "macos", feature = "platforms"))]
+    {
+        return makepad_shell_platforms::menu::macos::update_app_menu_macos(menu)
+            .map_err(|_| ShellError::Unsupported);
+    }
+    #[cfg(not(all(target_os = "macos", feature = "platforms")))]
+    {
+        let _ = menu;
         Err(ShellError::Unsupported)
     }
 }
 
+fn update_platform_app_menu_command(
+    id: CommandId,
+    update: impl FnMut(&mut CommandItem),
+) -> Result<(), ShellError> {
+    #[cfg(all(target_os = "macos", feature = "platforms"))]
+    {
+        return makepad_shell_platforms::menu::macos::update_app_menu_command_macos(id, update)
+            .map_err(|_| ShellError::Unsupported);
+    }
+    #[cfg(not(all(target_os = "macos", feature = "platforms")))]
+    {
+        let _ = id;
+        let _ = update;
+        Err(ShellError::Unsupported)
+    }
+}
+
+/// Also hands the backend the titles of the default app menu it adds when
+/// `menu` has none.
+fn localize_menu_bar(menu: &MenuBarModel) -> MenuBarModel {
+    Localization::with(|localizer| {
+        #[cfg(all(target_os = "macos", feature = "platforms"))]
+        {
+            use makepad_shell_core::locale::Message;
+            use makepad_shell_platforms::menu::macos;
+
+            let app = macos::process_name_macos();
+            let title = |key: &str| localizer.lookup(&Message::new(key).with_arg("app", &app));
+            macos::set_default_app_menu_titles_macos(
+                title("shell.app_menu.about"),
+                title("shell.app_menu.quit"),
+            );
+        }
+        localizer.localize_menu_bar(menu)
+    })
+}
+
+/// Resolves the app menu again after the locale changed.
+pub(crate) fn relocalize_app_menu() -> Result<(), ShellError> {
+    let Some(menu) = APP_MENU.with(|slot| slot.borrow().clone()) else {
+        return Ok(());
+    };
+    update_platform_app_menu(localize_menu_bar(&menu))
+}
+
 pub fn clear_app_menu() -> Result<(), ShellError> {
-    set_app_menu(MenuBarModel::new(Vec::new()), |_| {})
+    set_app_menu(MenuBarModel::new(Vec::new()), |_| {}).map(|_| ())
 }
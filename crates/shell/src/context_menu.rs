@@ -2,9 +2,13 @@
 use core::ffi::c_void;
 
 use makepad_shell_core::command::CommandId;
-use makepad_shell_core::menu::{MenuAnchor, MenuModel, MenuTrigger};
+use makepad_shell_core::menu::{
+    CommandStateProvider, MenuAnchor, MenuEvent, MenuModel, MenuTrigger,
+};
 
 use crate::ShellError;
+#[cfg(target_os = "macos")]
+use crate::{Localization, locale::LocalizedState};
 
 pub struct ContextMenu;
 
@@ -18,6 +22,29 @@ impl ContextMenu {
         popup_context_menu(menu, anchor, trigger, on_command)
     }
 
+    /// Like `popup`, but reports the menu opening, closing, highlighting and
+    /// being dismissed too.
+    pub fn popup_with_events(
+        menu: MenuModel,
+        anchor: MenuAnchor,
+        trigger: MenuTrigger,
+        on_event: impl Fn(MenuEvent) + 'static,
+    ) -> Result<(), ShellError> {
+        popup_context_menu_with_events(menu, anchor, trigger, on_event)
+    }
+
+    /// Like `popup_with_events`, but asks `state` for each command's label,
+    /// enabled and checked state before the menu shows.
+    pub fn popup_with_state(
+        menu: MenuModel,
+        anchor: MenuAnchor,
+        trigger: MenuTrigger,
+        on_event: impl Fn(MenuEvent) + 'static,
+        state: impl CommandStateProvider + 'static,
+    ) -> Result<(), ShellError> {
+        popup_context_menu_with_state(menu, anchor, trigger, on_event, state)
+    }
+
     #[cfg(target_os = "macos")]
     pub fn popup_macos(
         menu: MenuModel,
@@ -27,37 +54,115 @@ impl ContextMenu {
         ns_event: *mut c_void,
         on_command: impl Fn(CommandId) + 'static,
     ) -> Result<(), ShellError> {
-        #[cfg(feature = "platforms")]
-        {
-            return makepad_shell_platforms::menu::macos::popup_context_menu_macos(
-                menu,
-                anchor,
-                trigger,
-                ns_view,
-                ns_event,
-                Box::new(on_command),
-            )
-            .map_err(|_| ShellError::Unsupported);
-        }
-        #[cfg(not(feature = "platforms"))]
-        {
-            let _ = menu;
-            let _ = anchor;
-            let _ = trigger;
-            let _ = ns_view;
-            let _ = ns_event;
-            let _ = on_command;
-            return Err(ShellError::Unsupported);
-        }
+        Self::popup_macos_with_events(menu, anchor, trigger, ns_view, ns_event, move |event| {
+            if let Some(cmd) = event.command() {
+                on_command(cmd);
+            }
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn popup_macos_with_events(
+        menu: MenuModel,
+        anchor: MenuAnchor,
+        trigger: MenuTrigger,
+        ns_view: *mut c_void,
+        ns_event: *mut c_void,
+        on_event: impl Fn(MenuEvent) + 'static,
+    ) -> Result<(), ShellError> {
+        popup_macos_inner(
+            menu,
+            anchor,
+            trigger,
+            ns_view,
+            ns_event,
+            Box::new(on_event),
+            None,
+        )
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn popup_macos_with_state(
+        menu: MenuModel,
+        anchor: MenuAnchor,
+        trigger: MenuTrigger,
+        ns_view: *mut c_void,
+        ns_event: *mut c_void,
+        on_event: impl Fn(MenuEvent) + 'static,
+        state: impl CommandStateProvider + 'static,
+    ) -> Result<(), ShellError> {
+        popup_macos_inner(
+            menu,
+            anchor,
+            trigger,
+            ns_view,
+            ns_event,
+            Box::new(on_event),
+            Some(Box::new(LocalizedState(state))),
+        )
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn popup_macos_inner(
+    menu: MenuModel,
+    anchor: MenuAnchor,
+    trigger: MenuTrigger,
+    ns_view: *mut c_void,
+    ns_event: *mut c_void,
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+    state: Option<Box<dyn CommandStateProvider + 'static>>,
+) -> Result<(), ShellError> {
+    let menu = Localization::with(|localizer| localizer.localize_menu(&menu));
+    #[cfg(feature = "platforms")]
+    {
+        return makepad_shell_platforms::menu::macos::popup_context_menu_macos_with_events(
+            menu, anchor, trigger, ns_view, ns_event, on_event, state,
+        )
+        .map_err(|_| ShellError::Unsupported);
+    }
+    #[cfg(not(feature = "platforms"))]
+    {
+        let _ = menu;
+        let _ = anchor;
+        let _ = trigger;
+        let _ = ns_view;
+        let _ = ns_event;
+        let _ = on_event;
+        let _ = state;
+        return Err(ShellError::Unsupported);
     }
 }
 
 pub fn popup_context_menu(
+    menu: MenuModel,
+    anchor: MenuAnchor,
+    trigger: MenuTrigger,
+    on_command: impl Fn(CommandId) + 'static,
+) -> Result<(), ShellError> {
+    popup_context_menu_with_events(menu, anchor, trigger, move |event| {
+        if let Some(cmd) = event.command() {
+            on_command(cmd);
+        }
+    })
+}
+
+pub fn popup_context_menu_with_events(
     _menu: MenuModel,
     _anchor: MenuAnchor,
     _trigger: MenuTrigger,
-    _on_command: impl Fn(CommandId) + 'static,
+    _on_event: impl Fn(MenuEvent) + 'static,
 ) -> Result<(), ShellError> {
     // 未来这里会转发到 platforms
     Err(ShellError::Unsupported)
 }
+
+pub fn popup_context_menu_with_state(
+    _menu: MenuModel,
+    _anchor: MenuAnchor,
+    _trigger: MenuTrigger,
+    _on_event: impl Fn(MenuEvent) + 'static,
+    _state: impl CommandStateProvider + 'static,
+) -> Result<(), ShellError> {
+    Err(ShellError::Unsupported)
+}
@@ -1,24 +1,36 @@
 #[cfg(feature = "command")]
 pub use makepad_shell_core::command::*;
 pub use makepad_shell_core::error::ShellError;
+pub use makepad_shell_core::locale::*;
 #[cfg(feature = "menu-model")]
 pub use makepad_shell_core::menu::*;
 #[cfg(feature = "notification")]
 pub use makepad_shell_core::notification::*;
+pub use makepad_shell_core::platform::Platform;
 #[cfg(feature = "shortcut")]
 pub use makepad_shell_core::shortcut::*;
 #[cfg(feature = "tray")]
 pub use makepad_shell_core::tray::*;
+#[cfg(feature = "menu-model")]
+pub use makepad_shell_core::{menu, menu_bar};
+
+mod locale;
+pub use locale::Localization;
 
 #[cfg(feature = "context-menu")]
 mod context_menu;
 #[cfg(feature = "context-menu")]
-pub use context_menu::{ContextMenu, popup_context_menu};
+pub use context_menu::{
+    ContextMenu, popup_context_menu, popup_context_menu_with_events, popup_context_menu_with_state,
+};
 
 #[cfg(feature = "app-menu")]
 mod app_menu;
 #[cfg(feature = "app-menu")]
-pub use app_menu::{AppMenu, clear_app_menu, set_app_menu};
+pub use app_menu::{
+    AppMenu, AppMenuHandle, clear_app_menu, set_app_menu, set_app_menu_with_events,
+    set_app_menu_with_state,
+};
 
 #[cfg(feature = "tray")]
 mod tray;
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+
+use makepad_shell_core::locale::{Localizer, MessageTable};
+#[cfg(any(feature = "app-menu", feature = "context-menu", feature = "tray"))]
+use makepad_shell_core::{
+    command::CommandId,
+    menu::{CommandState, CommandStateProvider},
+};
+
+use crate::ShellError;
+
+thread_local! {
+    static LOCALIZER: RefCell<Option<Localizer>> = const { RefCell::new(None) };
+}
+
+/// The localizer for menus, trays and notifications shown through this
+/// crate.
+///
+/// Message labels (`Message`) are resolved whenever a model is set or shown,
+/// as are the labels a `CommandStateProvider` answers with. Without a
+/// localizer installed they still are, against an empty catalog: messages
+/// show their key and `@@` shows a literal `@`.
+///
+/// On macOS a menu bar without an app menu gets a default one. Its About and
+/// Quit items use the messages `shell.app_menu.about` and
+/// `shell.app_menu.quit` with the process name as argument `app`, or
+/// "About <app>" and "Quit <app>" if the catalog has no such message.
+/// Changing the locale resolves the app menu and every live tray again
+/// through their update paths, so only changed items are touched. Shown
+/// notifications keep their text; later ones use the new locale.
+pub struct Localization;
+
+impl Localization {
+    pub fn install(localizer: Localizer) -> Result<(), ShellError> {
+        LOCALIZER.with(|slot| *slot.borrow_mut() = Some(localizer));
+        relocalize()
+    }
+
+    /// Installed menus go back to showing message keys.
+    pub fn uninstall() -> Result<(), ShellError> {
+        LOCALIZER.with(|slot| *slot.borrow_mut() = None);
+        relocalize()
+    }
+
+    /// Does nothing while no localizer is installed.
+    pub fn set_locale(locale: impl Into<String>) -> Result<(), ShellError> {
+        let locale = locale.into();
+        let changed = LOCALIZER.with(|slot| match slot.borrow_mut().as_mut() {
+            Some(localizer) if localizer.locale() != locale => {
+                localizer.set_locale(locale);
+                true
+            }
+            _ => false,
+        });
+        if changed { relocalize() } else { Ok(()) }
+    }
+
+    /// `None` while no localizer is installed.
+    pub fn locale() -> Option<String> {
+        LOCALIZER.with(|slot| {
+            slot.borrow()
+                .as_ref()
+                .map(|localizer| localizer.locale().to_string())
+        })
+    }
+
+    /// The text for `label` in the current locale, e.g. for the items of a
+    /// lazy submenu.
+    pub fn resolve(label: &str) -> String {
+        Self::with(|localizer| localizer.resolve(label))
+    }
+
+    /// Runs `f` with the installed localizer, or with one whose catalog is
+    /// empty if none is installed.
+    pub fn with<R>(f: impl FnOnce(&Localizer) -> R) -> R {
+        LOCALIZER.with(|slot| match slot.borrow().as_ref() {
+            Some(localizer) => f(localizer),
+            None => f(&Localizer::new(MessageTable::new(), "", "")),
+        })
+    }
+}
+
+/// Resolves the labels `0` answers with.
+#[cfg(any(feature = "app-menu", feature = "context-menu", feature = "tray"))]
+pub(crate) struct LocalizedState<P>(pub(crate) P);
+
+#[cfg(any(feature = "app-menu", feature = "context-menu", feature = "tray"))]
+impl<P: CommandStateProvider> CommandStateProvider for LocalizedState<P> {
+    fn command_state(&self, id: CommandId) -> CommandState {
+        let mut state = self.0.command_state(id);
+        state.label = state.label.map(|label| Localization::resolve(&label));
+        state
+    }
+}
+
+fn relocalize() -> Result<(), ShellError> {
+    #[cfg(feature = "app-menu")]
+    crate::app_menu::relocalize_app_menu()?;
+    #[cfg(feature = "tray")]
+    crate::tray::relocalize_trays()?;
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use makepad_shell_core::command::CommandId;
 use makepad_shell_core::notification::Notification;
 
-use crate::ShellError;
+use crate::{Localization, ShellError};
 
 pub struct Notifications;
 
@@ -10,6 +10,8 @@ impl Notifications {
         notification: Notification,
         on_command: impl Fn(CommandId) + 'static,
     ) -> Result<(), ShellError> {
+        let notification =
+            Localization::with(|localizer| localizer.localize_notification(&notification));
         #[cfg(target_os = "macos")]
         {
             #[cfg(feature = "platforms")]
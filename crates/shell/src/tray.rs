@@ -1,74 +1,104 @@
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+use std::cell::RefCell;
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+use std::rc::{Rc, Weak};
+
 use makepad_shell_core::command::CommandId;
+use makepad_shell_core::menu::{CheckState, CommandItem, CommandStateProvider, MenuEvent};
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+use makepad_shell_core::menu::{auto_check_menu_items, update_command_items};
+use makepad_shell_core::shortcut::ShortcutSequence;
 use makepad_shell_core::tray::{TrayIcon, TrayMenuModel, TrayModel};
 
-use crate::ShellError;
+use crate::locale::LocalizedState;
+use crate::{Localization, ShellError};
+
+#[cfg(all(target_os = "macos", feature = "platforms"))]
+type PlatformTrayHandle = makepad_shell_platforms::tray::macos::MacTrayHandle;
+#[cfg(all(target_os = "windows", feature = "platforms"))]
+type PlatformTrayHandle = makepad_shell_platforms::tray::windows::WindowsTrayHandle;
+
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+thread_local! {
+    /// Every live tray, so a locale change can reach them.
+    static TRAYS: RefCell<Vec<Weak<RefCell<InstalledTray>>>> = const { RefCell::new(Vec::new()) };
+}
 
 pub struct TrayHandle {
-    #[cfg(all(target_os = "macos", feature = "platforms"))]
-    inner: makepad_shell_platforms::tray::macos::MacTrayHandle,
-    #[cfg(all(target_os = "windows", feature = "platforms"))]
-    inner: makepad_shell_platforms::tray::windows::WindowsTrayHandle,
+    #[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+    tray: Rc<RefCell<InstalledTray>>,
 }
 
 impl TrayHandle {
     pub fn update_menu(&mut self, menu: TrayMenuModel) -> Result<(), ShellError> {
-        #[cfg(all(target_os = "macos", feature = "platforms"))]
+        #[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
         {
-            return self
-                .inner
-                .update_menu(&menu)
-                .map_err(|_| ShellError::Unsupported);
+            return self.tray.borrow_mut().update_menu(menu);
         }
-        #[cfg(all(target_os = "windows", feature = "platforms"))]
-        {
-            return self
-                .inner
-                .update_menu(&menu)
-                .map_err(|_| ShellError::Unsupported);
-        }
-        #[cfg(all(target_os = "macos", not(feature = "platforms")))]
+        #[cfg(not(all(any(target_os = "macos", target_os = "windows"), feature = "platforms")))]
         {
             let _ = menu;
             Err(ShellError::Unsupported)
         }
-        #[cfg(all(target_os = "windows", not(feature = "platforms")))]
+    }
+
+    pub fn set_enabled(&mut self, id: CommandId, enabled: bool) -> Result<(), ShellError> {
+        self.update_command(id, |cmd| cmd.enabled = enabled)
+    }
+
+    pub fn set_checked(
+        &mut self,
+        id: CommandId,
+        checked: impl Into<CheckState>,
+    ) -> Result<(), ShellError> {
+        let checked = checked.into();
+        self.update_command(id, |cmd| cmd.checked = checked)
+    }
+
+    pub fn set_label(&mut self, id: CommandId, label: impl Into<String>) -> Result<(), ShellError> {
+        let label = label.into();
+        self.update_command(id, |cmd| cmd.label = label.clone())
+    }
+
+    pub fn set_shortcut(
+        &mut self,
+        id: CommandId,
+        shortcut: Option<ShortcutSequence>,
+    ) -> Result<(), ShellError> {
+        self.update_command(id, |cmd| cmd.shortcut = shortcut)
+    }
+
+    /// Runs `update` on every item of command `id` and changes only those
+    /// native items, unlike `update_menu`. A change the native menu cannot
+    /// make in place, e.g. a new role or radio group, rebuilds it.
+    pub fn update_command(
+        &mut self,
+        id: CommandId,
+        update: impl FnMut(&mut CommandItem),
+    ) -> Result<(), ShellError> {
+        #[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
         {
-            let _ = menu;
-            Err(ShellError::Unsupported)
+            return self.tray.borrow_mut().update_command(id, update);
         }
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(not(all(any(target_os = "macos", target_os = "windows"), feature = "platforms")))]
         {
-            let _ = menu;
+            let _ = id;
+            let _ = update;
             Err(ShellError::Unsupported)
         }
     }
 
     pub fn update_icon(&mut self, icon: TrayIcon) -> Result<(), ShellError> {
-        #[cfg(all(target_os = "macos", feature = "platforms"))]
-        {
-            return self
-                .inner
-                .update_icon(&icon)
-                .map_err(|_| ShellError::Unsupported);
-        }
-        #[cfg(all(target_os = "windows", feature = "platforms"))]
+        #[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
         {
             return self
+                .tray
+                .borrow_mut()
                 .inner
                 .update_icon(&icon)
                 .map_err(|_| ShellError::Unsupported);
         }
-        #[cfg(all(target_os = "macos", not(feature = "platforms")))]
-        {
-            let _ = icon;
-            Err(ShellError::Unsupported)
-        }
-        #[cfg(all(target_os = "windows", not(feature = "platforms")))]
-        {
-            let _ = icon;
-            Err(ShellError::Unsupported)
-        }
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(not(all(any(target_os = "macos", target_os = "windows"), feature = "platforms")))]
         {
             let _ = icon;
             Err(ShellError::Unsupported)
@@ -76,36 +106,90 @@ impl TrayHandle {
     }
 
     pub fn update_tooltip(&mut self, tooltip: Option<String>) -> Result<(), ShellError> {
-        #[cfg(all(target_os = "macos", feature = "platforms"))]
-        {
-            return self
-                .inner
-                .update_tooltip(tooltip.as_deref())
-                .map_err(|_| ShellError::Unsupported);
-        }
-        #[cfg(all(target_os = "windows", feature = "platforms"))]
+        #[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
         {
-            return self
-                .inner
-                .update_tooltip(tooltip.as_deref())
-                .map_err(|_| ShellError::Unsupported);
+            return self.tray.borrow_mut().update_tooltip(tooltip);
         }
-        #[cfg(all(target_os = "macos", not(feature = "platforms")))]
+        #[cfg(not(all(any(target_os = "macos", target_os = "windows"), feature = "platforms")))]
         {
             let _ = tooltip;
             Err(ShellError::Unsupported)
         }
-        #[cfg(all(target_os = "windows", not(feature = "platforms")))]
-        {
-            let _ = tooltip;
-            Err(ShellError::Unsupported)
-        }
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-        {
-            let _ = tooltip;
-            Err(ShellError::Unsupported)
+    }
+}
+
+/// A native tray with its menu and tooltip as given, before localization.
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+struct InstalledTray {
+    inner: PlatformTrayHandle,
+    /// Shared with the event handler, which mirrors auto-check into it.
+    menu: Rc<RefCell<TrayMenuModel>>,
+    tooltip: Option<String>,
+}
+
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+impl InstalledTray {
+    fn update_menu(&mut self, menu: TrayMenuModel) -> Result<(), ShellError> {
+        let localized = Localization::with(|localizer| localizer.localize_tray_menu(&menu));
+        self.inner
+            .update_menu(&localized)
+            .map_err(|_| ShellError::Unsupported)?;
+        *self.menu.borrow_mut() = menu;
+        Ok(())
+    }
+
+    fn update_command(
+        &mut self,
+        id: CommandId,
+        mut update: impl FnMut(&mut CommandItem),
+    ) -> Result<(), ShellError> {
+        // The native items show resolved labels and badges; resolve only new ones.
+        self.inner
+            .update_command(id, |cmd| {
+                let (label, badge) = (cmd.label.clone(), cmd.badge.clone());
+                update(cmd);
+                if cmd.label != label {
+                    cmd.label = Localization::resolve(&cmd.label);
+                }
+                if cmd.badge != badge {
+                    cmd.badge = cmd.badge.as_deref().map(Localization::resolve);
+                }
+            })
+            .map_err(|_| ShellError::Unsupported)?;
+        update_command_items(&mut self.menu.borrow_mut().items, id, &mut update);
+        Ok(())
+    }
+
+    fn update_tooltip(&mut self, tooltip: Option<String>) -> Result<(), ShellError> {
+        let localized = tooltip.as_deref().map(Localization::resolve);
+        self.inner
+            .update_tooltip(localized.as_deref())
+            .map_err(|_| ShellError::Unsupported)?;
+        self.tooltip = tooltip;
+        Ok(())
+    }
+
+    fn relocalize(&mut self) -> Result<(), ShellError> {
+        let menu = self.menu.borrow().clone();
+        self.update_menu(menu)?;
+        self.update_tooltip(self.tooltip.clone())
+    }
+}
+
+/// Resolves every live tray again after the locale changed.
+pub(crate) fn relocalize_trays() -> Result<(), ShellError> {
+    #[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+    {
+        let trays: Vec<_> = TRAYS.with(|trays| {
+            let mut trays = trays.borrow_mut();
+            trays.retain(|tray| tray.strong_count() > 0);
+            trays.iter().filter_map(Weak::upgrade).collect()
+        });
+        for tray in trays {
+            tray.borrow_mut().relocalize()?;
         }
     }
+    Ok(())
 }
 
 pub struct Tray;
@@ -116,22 +200,71 @@ impl Tray {
         on_command: impl Fn(CommandId) + 'static,
         on_activate: impl Fn() + 'static,
     ) -> Result<TrayHandle, ShellError> {
+        let on_event = move |event: MenuEvent| {
+            if let Some(cmd) = event.command() {
+                on_command(cmd);
+            }
+        };
+        Self::create_with_events(model, on_event, on_activate)
+    }
+
+    /// Like `create`, but reports the menu opening, closing, highlighting and
+    /// being dismissed too.
+    pub fn create_with_events(
+        model: TrayModel,
+        on_event: impl Fn(MenuEvent) + 'static,
+        on_activate: impl Fn() + 'static,
+    ) -> Result<TrayHandle, ShellError> {
+        Self::create_inner(model, Box::new(on_event), None, Box::new(on_activate))
+    }
+
+    /// Like `create_with_events`, but asks `state` for each command's label,
+    /// enabled and checked state before the menu shows.
+    pub fn create_with_state(
+        model: TrayModel,
+        on_event: impl Fn(MenuEvent) + 'static,
+        state: impl CommandStateProvider + 'static,
+        on_activate: impl Fn() + 'static,
+    ) -> Result<TrayHandle, ShellError> {
+        Self::create_inner(
+            model,
+            Box::new(on_event),
+            Some(Box::new(LocalizedState(state))),
+            Box::new(on_activate),
+        )
+    }
+
+    fn create_inner(
+        model: TrayModel,
+        on_event: Box<dyn Fn(MenuEvent) + 'static>,
+        state: Option<Box<dyn CommandStateProvider + 'static>>,
+        on_activate: Box<dyn Fn() + 'static>,
+    ) -> Result<TrayHandle, ShellError> {
+        let menu = model.menu.clone();
+        let tooltip = model.tooltip.clone();
+        let model = Localization::with(|localizer| localizer.localize_tray(&model));
         #[cfg(target_os = "macos")]
         {
             #[cfg(feature = "platforms")]
             {
-                let inner = makepad_shell_platforms::tray::macos::create_tray_macos(
+                let menu = Rc::new(RefCell::new(menu));
+                let on_event = mirror_auto_check(&menu, on_event);
+                let inner = makepad_shell_platforms::tray::macos::create_tray_macos_with_events(
                     model,
-                    Box::new(on_command),
-                    Box::new(on_activate),
+                    on_event,
+                    state,
+                    on_activate,
                 )
                 .map_err(|_| ShellError::Unsupported)?;
-                return Ok(TrayHandle { inner });
+                return Ok(TrayHandle::install(inner, menu, tooltip));
             }
             #[cfg(not(feature = "platforms"))]
             {
                 let _ = model;
-                let _ = on_command;
+                let _ = menu;
+                let _ = tooltip;
+                let _ = on_event;
+                let _ = state;
                 let _ = on_activate;
                 return Err(ShellError::Unsupported);
             }
@@ -140,18 +273,25 @@ impl Tray {
         {
             #[cfg(feature = "platforms")]
             {
-                let inner = makepad_shell_platforms::tray::windows::create_tray_windows(
-                    model,
-                    Box::new(on_command),
-                    Box::new(on_activate),
-                )
-                .map_err(|_| ShellError::Unsupported)?;
-                return Ok(TrayHandle { inner });
+                let menu = Rc::new(RefCell::new(menu));
+                let on_event = mirror_auto_check(&menu, on_event);
+                let inner =
+                    makepad_shell_platforms::tray::windows::create_tray_windows_with_events(
+                        model,
+                        on_event,
+                        state,
+                        on_activate,
+                    )
+                    .map_err(|_| ShellError::Unsupported)?;
+                return Ok(TrayHandle::install(inner, menu, tooltip));
             }
             #[cfg(not(feature = "platforms"))]
             {
                 let _ = model;
-                let _ = on_command;
+                let _ = menu;
+                let _ = tooltip;
+                let _ = on_event;
+                let _ = state;
                 let _ = on_activate;
                 return Err(ShellError::Unsupported);
             }
@@ -160,9 +300,48 @@ impl Tray {
         #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         {
             let _ = model;
-            let _ = on_command;
+            let _ = menu;
+            let _ = tooltip;
+            let _ = on_event;
+            let _ = state;
             let _ = on_activate;
             Err(ShellError::Unsupported)
         }
     }
 }
+
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+impl TrayHandle {
+    fn install(
+        inner: PlatformTrayHandle,
+        menu: Rc<RefCell<TrayMenuModel>>,
+        tooltip: Option<String>,
+    ) -> Self {
+        let tray = Rc::new(RefCell::new(InstalledTray {
+            inner,
+            menu,
+            tooltip,
+        }));
+        TRAYS.with(|trays| trays.borrow_mut().push(Rc::downgrade(&tray)));
+        Self { tray }
+    }
+}
+
+/// The backend auto-checks its own copy of the menu; doing the same to the
+/// source keeps a later relocalize from undoing it.
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "platforms"))]
+fn mirror_auto_check(
+    menu: &Rc<RefCell<TrayMenuModel>>,
+    on_event: Box<dyn Fn(MenuEvent) + 'static>,
+) -> Box<dyn Fn(MenuEvent) + 'static> {
+    let menu = Rc::downgrade(menu);
+    Box::new(move |event| {
+        if let MenuEvent::CommandInvoked(id) = event
+            && let Some(menu) = menu.upgrade()
+            && let Ok(mut menu) = menu.try_borrow_mut()
+        {
+            auto_check_menu_items(&mut menu.items, id);
+        }
+        on_event(event);
+    })
+}
@@ -3,8 +3,8 @@ use std::rc::Rc;
 
 use makepad_widgets::*;
 use makepad_shell::{
-    AppMenu, CommandId, CommandItem, ContextMenu, MenuAnchor, MenuBarModel, MenuItem,
-    MenuItemRole, MenuModel, MenuTrigger, Submenu, TopMenu, TopMenuRole,
+    AppMenu, CommandId, ContextMenu, MenuAnchor, MenuBarModel, MenuModel, MenuTrigger, menu,
+    menu_bar,
 };
 
 const CMD_COPY: u64 = 1;
@@ -189,77 +189,37 @@ impl AppMain for App {
 }
 
 fn build_menu(show_grid: bool) -> MenuModel {
-    let mut toggle_grid = CommandItem::new(CommandId::new(CMD_TOGGLE_GRID).unwrap(), "Show Grid");
-    toggle_grid.checked = show_grid;
-
-    let mut disabled = CommandItem::new(CommandId::new(CMD_DISABLED).unwrap(), "Disabled Item");
-    disabled.enabled = false;
-
-    MenuModel::new(vec![
-        MenuItem::Command(CommandItem::new(CommandId::new(CMD_COPY).unwrap(), "Copy")),
-        MenuItem::Command(CommandItem::new(CommandId::new(CMD_PASTE).unwrap(), "Paste")),
-        MenuItem::Command(CommandItem::new(CommandId::new(CMD_PASTE).unwrap(), "This is from makepad-shell-core")),
-        MenuItem::Separator,
-        MenuItem::Command(disabled),
-        MenuItem::Submenu(Submenu::new(
-            "View",
-            vec![
-                MenuItem::Command(toggle_grid),
-                MenuItem::Command(CommandItem::new(
-                    CommandId::new(CMD_ZOOM_TO_FIT).unwrap(),
-                    "Zoom to Fit",
-                )),
-            ],
-        )),
-    ])
+    menu![
+        command(CMD_COPY, "Copy"),
+        command(CMD_PASTE, "Paste"),
+        command(CMD_PASTE, "This is from makepad-shell-core"),
+        separator,
+        command(CMD_DISABLED, "Disabled Item", disabled),
+        submenu("View") {
+            command(CMD_TOGGLE_GRID, "Show Grid", checked = show_grid),
+            command(CMD_ZOOM_TO_FIT, "Zoom to Fit"),
+        },
+    ]
 }
 
 fn build_menu_bar(show_grid: bool) -> MenuBarModel {
-    let about = CommandItem::new(CommandId::new(CMD_ABOUT).unwrap(), "About Makepad Shell")
-        .with_role(MenuItemRole::About);
-    let prefs = CommandItem::new(CommandId::new(CMD_PREFERENCES).unwrap(), "Preferences…")
-        .with_role(MenuItemRole::Preferences);
-    let quit = CommandItem::new(CommandId::new(CMD_QUIT).unwrap(), "Quit Makepad Shell")
-        .with_role(MenuItemRole::Quit);
-
-    let mut grid_toggle =
-        CommandItem::new(CommandId::new(CMD_TOGGLE_GRID).unwrap(), "Show Grid");
-    grid_toggle.checked = show_grid;
-
-    let app_menu = TopMenu::new(
-        "Makepad Shell",
-        vec![
-            MenuItem::Command(about),
-            MenuItem::Separator,
-            MenuItem::Command(prefs),
-            MenuItem::Separator,
-            MenuItem::Command(quit),
-        ],
-    )
-    .with_role(TopMenuRole::App);
-
-    let file_menu = TopMenu::new(
-        "File",
-        vec![
-            MenuItem::Command(CommandItem::new(CommandId::new(CMD_COPY).unwrap(), "Copy")),
-            MenuItem::Command(CommandItem::new(CommandId::new(CMD_PASTE).unwrap(), "Paste")),
-        ],
-    )
-    .with_role(TopMenuRole::File);
-
-    let view_menu = TopMenu::new(
-        "View",
-        vec![
-            MenuItem::Command(grid_toggle),
-            MenuItem::Command(CommandItem::new(
-                CommandId::new(CMD_ZOOM_TO_FIT).unwrap(),
-                "Zoom to Fit",
-            )),
-        ],
-    )
-    .with_role(TopMenuRole::View);
-
-    MenuBarModel::new(vec![app_menu, file_menu, view_menu])
+    menu_bar![
+        menu("Makepad Shell", role = App) {
+            command(CMD_ABOUT, "About Makepad Shell", role = About),
+            separator,
+            command(CMD_PREFERENCES, "Preferences…", role = Preferences),
+            separator,
+            command(CMD_QUIT, "Quit Makepad Shell", role = Quit),
+        },
+        menu("File", role = File) {
+            command(CMD_COPY, "Copy"),
+            command(CMD_PASTE, "Paste"),
+        },
+        menu("View", role = View) {
+            command(CMD_TOGGLE_GRID, "Show Grid", checked = show_grid),
+            command(CMD_ZOOM_TO_FIT, "Zoom to Fit"),
+        },
+    ]
 }
 
 #[cfg(target_os = "macos")]
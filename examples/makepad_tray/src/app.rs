@@ -255,20 +255,23 @@ fn build_tray_menu(show_grid: bool, close_to_tray: bool) -> TrayMenuModel {
             ..Modifiers::default()
         }
     };
-    toggle_grid.checked = show_grid;
-    toggle_grid.shortcut = Some(Shortcut {
-        mods: shortcut_mods,
-        key: Key::Char('g'),
-    });
+    toggle_grid.checked = show_grid.into();
+    toggle_grid.shortcut = Some(
+        Shortcut {
+            mods: shortcut_mods,
+            key: Key::Char('g'),
+        }
+        .into(),
+    );
 
     let mut close_to_tray_item =
         TrayCommandItem::new(CommandId::new(CMD_CLOSE_TO_TRAY).unwrap(), "Close to Tray");
-    close_to_tray_item.checked = close_to_tray;
+    close_to_tray_item.checked = close_to_tray.into();
 
     TrayMenuModel::new(vec![
         TrayMenuItem::Command(toggle_grid),
         TrayMenuItem::Command(close_to_tray_item),
-        TrayMenuItem::Separator,
+        TrayMenuItem::separator(),
         TrayMenuItem::Command(
             TrayCommandItem::new(CommandId::new(CMD_QUIT).unwrap(), "Quit")
                 .with_role(TrayMenuItemRole::Quit),